@@ -1,46 +1,203 @@
 use super::*;
 use std::cmp::max;
+use std::ops::Range;
 use std::str::Chars;
 
-pub fn automaton_from_string(alphabet: &'static [char], string: &str) -> Automaton<char> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutomatonParseErrorKind {
+    MissingInitial,
+    MissingAccepting,
+    InvalidState,
+    MissingTransitionTarget,
+    InvalidSymbol,
+    SymbolNotInAlphabet,
+    TrailingTokens,
+}
+
+impl fmt::Display for AutomatonParseErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AutomatonParseErrorKind::MissingInitial => "missing initial state",
+            AutomatonParseErrorKind::MissingAccepting => "missing accepting states line",
+            AutomatonParseErrorKind::InvalidState => "invalid state index",
+            AutomatonParseErrorKind::MissingTransitionTarget => "missing transition target state",
+            AutomatonParseErrorKind::InvalidSymbol => "transition symbol must be a single char",
+            AutomatonParseErrorKind::SymbolNotInAlphabet => "symbol is not in the alphabet",
+            AutomatonParseErrorKind::TrailingTokens => "unexpected tokens at the end of line",
+        })
+    }
+}
+
+// `line` and `column` are 1-based, and `column` counts chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AutomatonParseError {
+    pub kind: AutomatonParseErrorKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for AutomatonParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        ))
+    }
+}
+
+impl std::error::Error for AutomatonParseError {}
+
+fn automaton_parse_error<T>(
+    kind: AutomatonParseErrorKind,
+    line: usize,
+    column: usize,
+) -> Result<T, AutomatonParseError> {
+    Err(AutomatonParseError { kind, line, column })
+}
+
+// Whitespace-separated tokens of `line` together with their columns. A token starting
+// with `#` starts a comment that runs to the end of the line.
+fn tokens_with_columns(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut token_start: Option<(usize, usize)> = None;
+    for (index, (offset, c)) in line.char_indices().enumerate() {
+        match token_start {
+            None if c == '#' => return tokens,
+            None if !c.is_whitespace() => token_start = Some((index + 1, offset)),
+            Some((column, start)) if c.is_whitespace() => {
+                tokens.push((column, &line[start..offset]));
+                token_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((column, start)) = token_start {
+        tokens.push((column, &line[start..]));
+    }
+    tokens
+}
+
+// The automaton allocates every state up to the largest index, so the indices are capped.
+const MAX_STATES: usize = 1 << 20;
+
+// Parses a state index and grows the automaton to include it.
+fn parse_state(
+    automaton: &mut Automaton<char>,
+    token: &str,
+    line: usize,
+    column: usize,
+) -> Result<usize, AutomatonParseError> {
+    let size = token
+        .parse::<usize>()
+        .ok()
+        .and_then(|state| state.checked_add(1));
+    match size {
+        Some(size) if size <= MAX_STATES => {
+            automaton.set_size(max(automaton.size(), size));
+            Ok(size - 1)
+        }
+        _ => automaton_parse_error(AutomatonParseErrorKind::InvalidState, line, column),
+    }
+}
+
+// Lines starting with `#` are comments. Blank lines are skipped everywhere except in place
+// of the accepting states, where a blank line means that no state is accepting.
+pub fn automaton_from_string(
+    alphabet: impl Into<Alphabet<char>>,
+    string: &str,
+) -> Result<Automaton<char>, AutomatonParseError> {
     let mut automaton = Automaton::new(alphabet, 1);
-    let mut lines = string.lines();
-    let initial: usize = lines.next().unwrap().parse().unwrap();
-    automaton.set_size(max(automaton.size(), initial + 1));
+    let end_line = string.lines().count() + 1;
+    let mut lines = string
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim_start().starts_with('#'))
+        .skip_while(|(_, line)| line.trim().is_empty());
+
+    let Some((line_number, line)) = lines.next() else {
+        return automaton_parse_error(AutomatonParseErrorKind::MissingInitial, end_line, 1);
+    };
+    let tokens = tokens_with_columns(line);
+    let (column, token) = tokens[0];
+    let initial = parse_state(&mut automaton, token, line_number, column)?;
+    if let Some((column, _)) = tokens.get(1) {
+        return automaton_parse_error(
+            AutomatonParseErrorKind::TrailingTokens,
+            line_number,
+            *column,
+        );
+    }
     automaton.set_initial(initial);
-    let accepting: Vec<usize> = lines.next().unwrap().split(' ').map(|token| {
-        token.parse().unwrap()
-    }).collect();
-    for accepting_state in accepting {
-        automaton.set_size(max(automaton.size(), accepting_state + 1));
+
+    let Some((line_number, line)) = lines.next() else {
+        return automaton_parse_error(AutomatonParseErrorKind::MissingAccepting, end_line, 1);
+    };
+    for (column, token) in tokens_with_columns(line) {
+        let accepting_state = parse_state(&mut automaton, token, line_number, column)?;
         automaton.set_accepting(accepting_state, true);
     }
-    for line in lines {
-        let tokens = line.split(' ').collect::<Vec<_>>();
-        let from: usize = tokens[0].parse().unwrap();
-        let to: usize = tokens[1].parse().unwrap();
-        automaton.set_size(max(automaton.size(), from + 1));
-        automaton.set_size(max(automaton.size(), to + 1));
-        if tokens.len() == 2 {
+
+    for (line_number, line) in lines {
+        let tokens = tokens_with_columns(line);
+        match tokens[..] {
+            [] => continue,
+            [(column, token)] => {
+                parse_state(&mut automaton, token, line_number, column)?;
+                let column = column + token.chars().count();
+                return automaton_parse_error(
+                    AutomatonParseErrorKind::MissingTransitionTarget,
+                    line_number,
+                    column,
+                );
+            }
+            [_, _, _, (column, _), ..] => {
+                return automaton_parse_error(
+                    AutomatonParseErrorKind::TrailingTokens,
+                    line_number,
+                    column,
+                );
+            }
+            _ => {}
+        }
+        let from = parse_state(&mut automaton, tokens[0].1, line_number, tokens[0].0)?;
+        let to = parse_state(&mut automaton, tokens[1].1, line_number, tokens[1].0)?;
+        let Some((column, symbol)) = tokens.get(2) else {
             automaton.add_empty_transition(from, to);
-        } else {
-            assert_eq!(tokens[2].len(), 1);
-            let c = &tokens[2].chars().next().unwrap();
-            assert!(alphabet.contains(c));
-            automaton.add_symbol_transition(from, to, c.clone());
+            continue;
+        };
+        let mut chars = symbol.chars();
+        let c = chars.next().expect("tokens are not empty");
+        if chars.next().is_some() {
+            return automaton_parse_error(
+                AutomatonParseErrorKind::InvalidSymbol,
+                line_number,
+                *column,
+            );
+        }
+        if !automaton.alphabet().contains(&c) {
+            return automaton_parse_error(
+                AutomatonParseErrorKind::SymbolNotInAlphabet,
+                line_number,
+                *column,
+            );
         }
+        automaton.add_symbol_transition(from, to, c);
     }
-    automaton
+    Ok(automaton)
 }
 
 pub fn automaton_to_string(automaton: &Automaton<char>) -> String {
     let mut result = String::new();
     result += &format!("{}\n", automaton.initial());
-    result += &format!("{}\n", (0..automaton.size()).into_iter()
-        .filter(|state| automaton.accepting(*state))
-        .map(|state| state.to_string())
-        .collect::<Vec<_>>()
-        .join(" "));
+    result += &format!(
+        "{}\n",
+        (0..automaton.size())
+            .filter(|state| automaton.accepting(*state))
+            .map(|state| state.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
     for state in 0..automaton.size() {
         for transition in automaton.transitions(state) {
             if let Some(c) = transition.symbol {
@@ -83,59 +240,460 @@ pub fn accepted_str(automaton: &Automaton<char>, word: &str) -> bool {
     automaton.accepted(&chars[..])
 }
 
-fn parse_regex_block_from_chars(chars: &mut Chars) -> Regex<char> {
+// Byte offset of every char of `text`, followed by the length of `text`.
+fn char_offsets(text: &str) -> Vec<usize> {
+    text.char_indices()
+        .map(|(offset, _)| offset)
+        .chain([text.len()])
+        .collect()
+}
+
+pub fn find_str(automaton: &Automaton<char>, text: &str) -> Option<Range<usize>> {
+    find_str_with(automaton, text, MatchKind::LeftmostLongest)
+}
+
+pub fn find_str_with(
+    automaton: &Automaton<char>,
+    text: &str,
+    kind: MatchKind,
+) -> Option<Range<usize>> {
+    let chars: Vec<char> = text.chars().collect();
+    let offsets = char_offsets(text);
+    automaton
+        .find_with(&chars[..], kind)
+        .map(|found| offsets[found.start]..offsets[found.end])
+}
+
+// Matches of `find_all_str` as byte ranges. The matches come in order, so char
+// positions are turned into byte offsets by walking the text forward.
+pub struct StrMatches<'a> {
+    searcher: search::Searcher<'a, char>,
+    chars: Vec<char>,
+    position: usize,
+    kind: MatchKind,
+    char_index: usize,
+    byte_offset: usize,
+}
+
+impl StrMatches<'_> {
+    fn byte_offset(&mut self, char_index: usize) -> usize {
+        self.byte_offset += self.chars[self.char_index..char_index]
+            .iter()
+            .map(|c| c.len_utf8())
+            .sum::<usize>();
+        self.char_index = char_index;
+        self.byte_offset
+    }
+}
+
+impl Iterator for StrMatches<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        let found = self
+            .searcher
+            .next_match(&self.chars, &mut self.position, self.kind)?;
+        let start = self.byte_offset(found.start);
+        let end = self.byte_offset(found.end);
+        Some(start..end)
+    }
+}
+
+pub fn find_all_str<'a>(automaton: &'a Automaton<char>, text: &str) -> StrMatches<'a> {
+    find_all_str_with(automaton, text, MatchKind::LeftmostLongest)
+}
+
+pub fn find_all_str_with<'a>(
+    automaton: &'a Automaton<char>,
+    text: &str,
+    kind: MatchKind,
+) -> StrMatches<'a> {
+    StrMatches {
+        searcher: search::Searcher::new(automaton),
+        chars: text.chars().collect(),
+        position: 0,
+        kind,
+        char_index: 0,
+        byte_offset: 0,
+    }
+}
+
+pub fn is_match_anywhere_str(automaton: &Automaton<char>, text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    automaton.is_match_anywhere(&chars[..])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegexParseErrorKind {
+    // A postfix operator such as `*` or `{2}` with nothing to apply to.
+    MissingOperand,
+    // An empty alternative, as in `a||b`, `|a` or an empty regex.
+    EmptyExpression,
+    EmptyGroup,
+    UnmatchedParenthesis,
+    UnclosedParenthesis,
+    UnclosedClass,
+    UnclosedRepetition,
+    InvalidRepetition,
+    // A bound of `{m,n}` above `MAX_REPETITION`, or repetitions whose expansion
+    // together exceeds `MAX_REPEATED_SIZE`.
+    RepetitionTooLarge,
+    // A class range such as `z-a` whose end comes before its start.
+    InvalidRange,
+    // More than `MAX_DEPTH` nested groups, or `~` repeated more than `MAX_DEPTH` times.
+    TooDeep,
+    DanglingEscape,
+}
+
+impl fmt::Display for RegexParseErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            RegexParseErrorKind::MissingOperand => "operator has nothing to apply to",
+            RegexParseErrorKind::EmptyExpression => "empty expression",
+            RegexParseErrorKind::EmptyGroup => "empty group",
+            RegexParseErrorKind::UnmatchedParenthesis => "unmatched closing parenthesis",
+            RegexParseErrorKind::UnclosedParenthesis => "unclosed parenthesis",
+            RegexParseErrorKind::UnclosedClass => "unclosed character class",
+            RegexParseErrorKind::UnclosedRepetition => "unclosed repetition",
+            RegexParseErrorKind::InvalidRepetition => "invalid repetition bounds",
+            RegexParseErrorKind::RepetitionTooLarge => "repetition count is too large",
+            RegexParseErrorKind::InvalidRange => "invalid class range",
+            RegexParseErrorKind::TooDeep => "groups are nested too deeply",
+            RegexParseErrorKind::DanglingEscape => "escape at the end of regex",
+        })
+    }
+}
+
+// `position` counts chars, not bytes, from the start of the regex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegexParseError {
+    pub kind: RegexParseErrorKind,
+    pub position: usize,
+}
+
+impl RegexParseError {
+    // The regex with a caret under the offending char and the error message after it.
+    pub fn render(&self, regex: &str) -> String {
+        format!("{}\n{}^ {}", regex, " ".repeat(self.position), self.kind)
+    }
+}
+
+impl fmt::Display for RegexParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{} at position {}", self.kind, self.position))
+    }
+}
+
+impl std::error::Error for RegexParseError {}
+
+// Every repetition is expanded into copies of the repeated regex, so the counts and
+// the total size of the copies are capped, as is the nesting of groups.
+const MAX_REPETITION: usize = 1000;
+const MAX_REPEATED_SIZE: usize = 100_000;
+const MAX_DEPTH: usize = 256;
+
+struct RegexInput<'a> {
+    chars: Chars<'a>,
+    position: usize,
+    // The size of the regex parsed so far with repetitions expanded, and the part of
+    // it that comes from the copies made by repetitions.
+    size: usize,
+    repeated_size: usize,
+    depth: usize,
+}
+
+impl RegexInput<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    // Accounts for `copies` copies of the operand that started when the size was `start`.
+    fn repeat(
+        &mut self,
+        start: usize,
+        copies: usize,
+        position: usize,
+    ) -> Result<(), RegexParseError> {
+        let width = self.size - start;
+        match width.checked_mul(copies.saturating_sub(1)) {
+            Some(added) if added <= MAX_REPEATED_SIZE - self.repeated_size => {
+                self.size += added;
+                self.repeated_size += added;
+                Ok(())
+            }
+            _ => regex_parse_error(RegexParseErrorKind::RepetitionTooLarge, position),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    // Skips the whitespace only if one of `operators` comes right after it.
+    fn skip_whitespace_before(&mut self, operators: &[char]) {
+        let mut lookahead = self.chars.clone().skip_while(|c| c.is_whitespace());
+        if lookahead.next().is_some_and(|c| operators.contains(&c)) {
+            self.skip_whitespace();
+        }
+    }
+}
+
+fn regex_parse_error<T>(kind: RegexParseErrorKind, position: usize) -> Result<T, RegexParseError> {
+    Err(RegexParseError { kind, position })
+}
+
+fn parse_escaped_char(input: &mut RegexInput) -> Result<char, RegexParseError> {
+    let position = input.position - 1;
+    match input.next() {
+        Some(c) => Ok(c),
+        None => regex_parse_error(RegexParseErrorKind::DanglingEscape, position),
+    }
+}
+
+fn parse_class_symbol(input: &mut RegexInput, c: char) -> Result<char, RegexParseError> {
+    if c == '\\' {
+        parse_escaped_char(input)
+    } else {
+        Ok(c)
+    }
+}
+
+fn parse_class_from_chars(input: &mut RegexInput) -> Result<Regex<char>, RegexParseError> {
+    let open = input.position - 1;
+    let negated = input.peek() == Some('^');
+    if negated {
+        input.next();
+    }
+    let mut symbols: Vec<char> = vec![];
+    loop {
+        let start = input.position;
+        let first = match input.next() {
+            None => return regex_parse_error(RegexParseErrorKind::UnclosedClass, open),
+            Some(']') => break,
+            Some(c) => parse_class_symbol(input, c)?,
+        };
+        let mut lookahead = input.chars.clone();
+        if lookahead.next() == Some('-') && lookahead.next().is_some_and(|c| c != ']') {
+            input.next();
+            let c = input.next().expect("range end is present");
+            let last = parse_class_symbol(input, c)?;
+            if last < first {
+                return regex_parse_error(RegexParseErrorKind::InvalidRange, start);
+            }
+            symbols.extend(first..=last);
+        } else {
+            symbols.push(first);
+        }
+    }
+    let mut seen = HashSet::new();
+    symbols.retain(|c| seen.insert(*c));
+    if negated {
+        Ok(Regex::NegatedClass(symbols))
+    } else if symbols.is_empty() {
+        Ok(Regex::Empty)
+    } else {
+        Ok(Regex::Class(symbols))
+    }
+}
+
+fn parse_repetition_bounds_from_chars(
+    input: &mut RegexInput,
+) -> Result<(usize, Option<usize>), RegexParseError> {
+    let open = input.position - 1;
+    let mut bounds = String::new();
+    loop {
+        match input.next() {
+            None => return regex_parse_error(RegexParseErrorKind::UnclosedRepetition, open),
+            Some('}') => break,
+            Some(c) => bounds.push(c),
+        }
+    }
+    let parsed = match bounds.split_once(',') {
+        Some((min, "")) => min.parse().map(|min| (min, None)),
+        Some((min, max)) => min
+            .parse()
+            .and_then(|min| max.parse().map(|max| (min, Some(max)))),
+        None => bounds.parse().map(|count| (count, Some(count))),
+    };
+    match parsed {
+        Ok((min, Some(max))) if max < min => {
+            regex_parse_error(RegexParseErrorKind::InvalidRepetition, open)
+        }
+        Ok((min, max)) if max.unwrap_or(min) > MAX_REPETITION => {
+            regex_parse_error(RegexParseErrorKind::RepetitionTooLarge, open)
+        }
+        Ok(parsed) => Ok(parsed),
+        Err(_) => regex_parse_error(RegexParseErrorKind::InvalidRepetition, open),
+    }
+}
+
+// Parses up to the end of the input, or up to the `)` matching the `(` at `open`.
+// From the weakest binding: `|`, `&`, concatenation, prefix `~`, postfix operators.
+// Whitespace around `|` and `&` and after `~` is skipped; elsewhere it is a literal symbol.
+fn parse_regex_block_from_chars(
+    input: &mut RegexInput,
+    open: Option<usize>,
+) -> Result<Regex<char>, RegexParseError> {
     let mut last_union_block: Option<Regex<char>> = None;
+    let mut last_intersection_block: Option<Regex<char>> = None;
     let mut last_concat_block: Option<Regex<char>> = None;
     let mut last_kleene_block: Option<Regex<char>> = None;
+    let mut last_kleene_block_start = input.size;
+    // How many times to complement the last kleene block once its postfix operators are applied.
+    let mut last_kleene_block_complements = 0;
+    // Position of the first `~` waiting for its operand, and the number of those `~`.
+    let mut pending_complements: Option<(usize, usize)> = None;
     loop {
-        if let Some(c) = chars.next() {
-            if c == ')' {
-                break;
-            } else if c == '|' {
-                if let Some(last_concat_block_regex) = last_concat_block {
-                    last_concat_block = Some(Regex::concat(last_concat_block_regex, last_kleene_block.unwrap()));
-                } else {
-                    last_concat_block = last_kleene_block;
+        input.skip_whitespace_before(&['|', '&']);
+        let position = input.position;
+        let c = input.next();
+        if let (
+            Some((complement_position, _)),
+            None | Some('|' | '&' | ')' | '*' | '+' | '?' | '{'),
+        ) = (pending_complements, c)
+        {
+            return regex_parse_error(RegexParseErrorKind::MissingOperand, complement_position);
+        }
+        if matches!(c, None | Some('|') | Some('&') | Some(')')) {
+            match (c, open) {
+                (Some(')'), None) => {
+                    return regex_parse_error(RegexParseErrorKind::UnmatchedParenthesis, position)
                 }
-                if let Some(last_union_block_regex) = last_union_block {
-                    last_union_block = Some(Regex::union(last_union_block_regex, last_concat_block.unwrap()));
-                } else {
-                    last_union_block = last_concat_block;
+                (None, Some(open)) => {
+                    return regex_parse_error(RegexParseErrorKind::UnclosedParenthesis, open)
                 }
-                last_concat_block = None;
-                last_kleene_block = None;
-            } else if c == '*' {
-                last_kleene_block = Some(Regex::kleene_star(last_kleene_block.unwrap()));
-            } else {
-                let block = if c == '(' {
-                    parse_regex_block_from_chars(chars)
-                } else {
-                    Regex::String(vec![c])
+                _ => {}
+            }
+            if let Some(last_kleene_block_regex) = last_kleene_block.take() {
+                let last_kleene_block_regex =
+                    complemented(last_kleene_block_regex, last_kleene_block_complements);
+                last_concat_block = Some(match last_concat_block {
+                    Some(last_concat_block_regex) => {
+                        Regex::concat(last_concat_block_regex, last_kleene_block_regex)
+                    }
+                    None => last_kleene_block_regex,
+                });
+            }
+            let Some(last_concat_block_regex) = last_concat_block.take() else {
+                return match (c, open) {
+                    (Some(')'), Some(open))
+                        if last_union_block.is_none() && last_intersection_block.is_none() =>
+                    {
+                        regex_parse_error(RegexParseErrorKind::EmptyGroup, open)
+                    }
+                    _ => regex_parse_error(RegexParseErrorKind::EmptyExpression, position),
                 };
-                if let Some(last_concat_block_regex) = last_concat_block {
-                    last_concat_block = Some(Regex::concat(last_concat_block_regex, last_kleene_block.unwrap()));
-                } else {
-                    last_concat_block = last_kleene_block;
+            };
+            last_intersection_block = Some(match last_intersection_block {
+                Some(last_intersection_block_regex) => {
+                    Regex::intersection(last_intersection_block_regex, last_concat_block_regex)
                 }
-                last_kleene_block = Some(block);
+                None => last_concat_block_regex,
+            });
+            if c == Some('&') {
+                input.skip_whitespace();
+                continue;
             }
-        } else {
-            break;
+            let last_intersection_block_regex = last_intersection_block
+                .take()
+                .expect("intersection block was just set");
+            last_union_block = Some(match last_union_block {
+                Some(last_union_block_regex) => {
+                    Regex::union(last_union_block_regex, last_intersection_block_regex)
+                }
+                None => last_intersection_block_regex,
+            });
+            if c != Some('|') {
+                return Ok(last_union_block.expect("union block was just set"));
+            }
+            input.skip_whitespace();
+            continue;
         }
+        let c = c.expect("end of input is handled above");
+        if c == '~' {
+            let (complement_position, count) = pending_complements.unwrap_or((position, 0));
+            if count == MAX_DEPTH {
+                return regex_parse_error(RegexParseErrorKind::TooDeep, position);
+            }
+            pending_complements = Some((complement_position, count + 1));
+            input.skip_whitespace();
+            continue;
+        }
+        if matches!(c, '*' | '+' | '?' | '{') {
+            let Some(last_kleene_block_regex) = last_kleene_block.take() else {
+                return regex_parse_error(RegexParseErrorKind::MissingOperand, position);
+            };
+            last_kleene_block = Some(match c {
+                '*' => Regex::kleene_star(last_kleene_block_regex),
+                '+' => {
+                    input.repeat(last_kleene_block_start, 2, position)?;
+                    Regex::plus(last_kleene_block_regex)
+                }
+                '?' => Regex::optional(last_kleene_block_regex),
+                _ => {
+                    let (min, max) = parse_repetition_bounds_from_chars(input)?;
+                    // `r{m,}` holds m + 1 copies of r and `r{m,n}` holds n.
+                    let copies = max.unwrap_or(min + 1);
+                    input.repeat(last_kleene_block_start, copies, position)?;
+                    Regex::repeat(last_kleene_block_regex, min, max)
+                }
+            });
+            continue;
+        }
+        let block_start = input.size;
+        input.size += 1;
+        let block = match c {
+            '(' => {
+                if input.depth == MAX_DEPTH {
+                    return regex_parse_error(RegexParseErrorKind::TooDeep, position);
+                }
+                input.depth += 1;
+                let group = parse_regex_block_from_chars(input, Some(position))?;
+                input.depth -= 1;
+                group
+            }
+            '[' => parse_class_from_chars(input)?,
+            '.' => Regex::NegatedClass(vec![]),
+            'ε' => Regex::Epsilon,
+            '∅' => Regex::Empty,
+            '\\' => Regex::String(vec![parse_escaped_char(input)?]),
+            c => Regex::String(vec![c]),
+        };
+        if let Some(last_kleene_block_regex) = last_kleene_block {
+            let last_kleene_block_regex =
+                complemented(last_kleene_block_regex, last_kleene_block_complements);
+            last_concat_block = Some(match last_concat_block {
+                Some(last_concat_block_regex) => {
+                    Regex::concat(last_concat_block_regex, last_kleene_block_regex)
+                }
+                None => last_kleene_block_regex,
+            });
+        }
+        last_kleene_block = Some(block);
+        last_kleene_block_start = block_start;
+        last_kleene_block_complements = pending_complements.take().map_or(0, |(_, count)| count);
     }
-    if let Some(last_concat_block_regex) = last_concat_block {
-        last_concat_block = Some(Regex::concat(last_concat_block_regex, last_kleene_block.unwrap()));
-    } else {
-        last_concat_block = last_kleene_block;
-    }
-    if let Some(last_union_block_regex) = last_union_block {
-        last_union_block = Some(Regex::union(last_union_block_regex, last_concat_block.unwrap()));
-    } else {
-        last_union_block = last_concat_block;
-    }
-    last_union_block.unwrap()
 }
 
-pub fn parse_regex_from_string(string: &str) -> Regex<char> {
-    parse_regex_block_from_chars(&mut string.chars())
+fn complemented(regex: Regex<char>, count: usize) -> Regex<char> {
+    (0..count).fold(regex, |regex, _| Regex::complement(regex))
+}
+
+pub fn parse_regex_from_string(string: &str) -> Result<Regex<char>, RegexParseError> {
+    let mut input = RegexInput {
+        chars: string.chars(),
+        position: 0,
+        size: 0,
+        repeated_size: 0,
+        depth: 0,
+    };
+    parse_regex_block_from_chars(&mut input, None)
 }
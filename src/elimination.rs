@@ -0,0 +1,165 @@
+use super::*;
+
+// The order in which `Automaton::regex_with` eliminates states. The greedy orders
+// pick the next state among the remaining ones after every elimination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EliminationOrder {
+    // States in index order.
+    Index,
+    // The state with the fewest paths through it, that is predecessors times successors.
+    FewestEdges,
+    // The state whose elimination grows the total size of the edge regexes the least,
+    // as estimated by Delgado and Morais.
+    #[default]
+    DelgadoMorais,
+}
+
+// The number of symbol occurrences in the regex.
+fn width<S: Eq + Clone + Hash + 'static>(regex: &Regex<S>) -> usize {
+    match regex {
+        Regex::Empty | Regex::Epsilon => 0,
+        Regex::String(vec) => vec.len(),
+        Regex::Class(_) | Regex::NegatedClass(_) => 1,
+        Regex::Concat(lhs, rhs) | Regex::Union(lhs, rhs) | Regex::Intersection(lhs, rhs) => {
+            width(lhs) + width(rhs)
+        }
+        Regex::Complement(regex) | Regex::KleeneStar(regex) => width(regex),
+    }
+}
+
+// Generalized automaton whose edges are labelled by regexes. The extra state `size`
+// is the single final state, reached by ε from every accepting state.
+struct EdgeRegexes<S: Eq + Clone + Hash + 'static> {
+    edges: Vec<HashMap<usize, Regex<S>>>,
+    eliminated: Vec<bool>,
+}
+
+impl<S: Eq + Clone + Hash + 'static> EdgeRegexes<S> {
+    fn new(automaton: &Automaton<S>) -> EdgeRegexes<S> {
+        let edges = (0..automaton.size())
+            .map(|state| {
+                let mut edges: HashMap<usize, Regex<S>> = HashMap::new();
+                for transition in automaton.transitions(state) {
+                    let regex = match transition.symbol {
+                        Some(symbol) => Regex::String(vec![symbol]),
+                        None => Regex::Epsilon,
+                    };
+                    let edge = match edges.remove(&transition.next_state) {
+                        Some(edge_regex) => Regex::union(edge_regex, regex),
+                        None => regex,
+                    };
+                    edges.insert(transition.next_state, edge);
+                }
+                if automaton.accepting(state) {
+                    edges.insert(automaton.size(), Regex::Epsilon);
+                }
+                edges
+            })
+            .collect();
+        EdgeRegexes {
+            edges,
+            eliminated: vec![false; automaton.size()],
+        }
+    }
+
+    fn predecessors(&self, state: usize) -> Vec<usize> {
+        (0..self.edges.len())
+            .filter(|from| {
+                *from != state && !self.eliminated[*from] && self.edges[*from].contains_key(&state)
+            })
+            .collect()
+    }
+
+    fn successors(&self, state: usize) -> Vec<usize> {
+        let mut successors: Vec<usize> = self.edges[state]
+            .keys()
+            .copied()
+            .filter(|to| *to != state)
+            .collect();
+        successors.sort();
+        successors
+    }
+
+    fn cost(&self, state: usize, order: EliminationOrder) -> usize {
+        let predecessors = self.predecessors(state);
+        let successors = self.successors(state);
+        match order {
+            EliminationOrder::Index => state,
+            EliminationOrder::FewestEdges => predecessors.len() * successors.len(),
+            EliminationOrder::DelgadoMorais => {
+                let incoming: usize = predecessors
+                    .iter()
+                    .map(|from| width(&self.edges[*from][&state]))
+                    .sum();
+                let outgoing: usize = successors
+                    .iter()
+                    .map(|to| width(&self.edges[state][to]))
+                    .sum();
+                let self_loop = self.edges[state].get(&state).map_or(0, width);
+                (incoming * successors.len().saturating_sub(1)
+                    + outgoing * predecessors.len().saturating_sub(1))
+                .saturating_add(
+                    self_loop * (predecessors.len() * successors.len()).saturating_sub(1),
+                )
+            }
+        }
+    }
+
+    fn eliminate(&mut self, state: usize) {
+        let self_loop = self.edges[state]
+            .get(&state)
+            .cloned()
+            .map(Regex::kleene_star);
+        let outgoing: Vec<(usize, Regex<S>)> = self
+            .successors(state)
+            .into_iter()
+            .map(|to| (to, self.edges[state][&to].clone()))
+            .collect();
+        for from in self.predecessors(state) {
+            let incoming = self.edges[from]
+                .remove(&state)
+                .expect("predecessor has an edge to state");
+            for (to, regex) in &outgoing {
+                let through = match &self_loop {
+                    Some(self_loop) => Regex::concat(
+                        incoming.clone(),
+                        Regex::concat(self_loop.clone(), regex.clone()),
+                    ),
+                    None => Regex::concat(incoming.clone(), regex.clone()),
+                };
+                let edge = match self.edges[from].remove(to) {
+                    Some(edge_regex) => Regex::union(edge_regex, through),
+                    None => through,
+                };
+                self.edges[from].insert(*to, edge);
+            }
+        }
+        self.eliminated[state] = true;
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
+    pub fn regex_with(&self, order: EliminationOrder) -> Regex<S> {
+        let (automaton, _) = Automaton::trim_of(self);
+        let mut edge_regexes = EdgeRegexes::new(&automaton);
+        let mut remaining: Vec<usize> = (0..automaton.size())
+            .filter(|state| *state != automaton.initial())
+            .collect();
+        while !remaining.is_empty() {
+            let index = (0..remaining.len())
+                .min_by_key(|index| edge_regexes.cost(remaining[*index], order))
+                .expect("remaining states are not empty");
+            edge_regexes.eliminate(remaining.remove(index));
+        }
+        let initial_edges = &edge_regexes.edges[automaton.initial()];
+        let Some(accepted) = initial_edges.get(&automaton.size()) else {
+            return Regex::Empty;
+        };
+        match initial_edges.get(&automaton.initial()) {
+            Some(self_loop) => {
+                Regex::concat(Regex::kleene_star(self_loop.clone()), accepted.clone())
+            }
+            None => accepted.clone(),
+        }
+    }
+}
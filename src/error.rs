@@ -0,0 +1,147 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AutomatonError<S> {
+    StateOutOfBounds {
+        state: usize,
+        size: usize,
+    },
+    // The accepting flags and the transition lists describe a different number of states.
+    SizeMismatch {
+        accepting: usize,
+        transitions: usize,
+    },
+    SizeDecrease {
+        size: usize,
+        new_size: usize,
+    },
+    SymbolNotInAlphabet(S),
+}
+
+impl<S: fmt::Debug> fmt::Display for AutomatonError<S> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            AutomatonError::StateOutOfBounds { state, size } => f.write_fmt(format_args!(
+                "state {} out of bounds for size {}",
+                state, size
+            )),
+            AutomatonError::SizeMismatch {
+                accepting,
+                transitions,
+            } => f.write_fmt(format_args!(
+                "size mismatch: {} accepting flags, {} transition lists",
+                accepting, transitions
+            )),
+            AutomatonError::SizeDecrease { size, new_size } => f.write_fmt(format_args!(
+                "cannot decrease size from {} to {}",
+                size, new_size
+            )),
+            AutomatonError::SymbolNotInAlphabet(symbol) => {
+                f.write_fmt(format_args!("symbol {:?} is not in the alphabet", symbol))
+            }
+        }
+    }
+}
+
+impl<S: fmt::Debug> std::error::Error for AutomatonError<S> {}
+
+fn check_state<S>(state: usize, size: usize) -> Result<(), AutomatonError<S>> {
+    if state >= size {
+        return Err(AutomatonError::StateOutOfBounds { state, size });
+    }
+    Ok(())
+}
+
+fn check_symbol<S: Eq + Clone + Hash + 'static>(
+    alphabet: &Alphabet<S>,
+    symbol: &Option<S>,
+) -> Result<(), AutomatonError<S>> {
+    match symbol {
+        Some(symbol) if !alphabet.contains(symbol) => {
+            Err(AutomatonError::SymbolNotInAlphabet(symbol.clone()))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Unlike their panicking counterparts, these also reject symbols outside the alphabet.
+impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
+    pub fn try_from(
+        alphabet: impl Into<Alphabet<S>>,
+        initial: usize,
+        accepting: Vec<bool>,
+        transitions: Vec<Vec<Transition<S>>>,
+    ) -> Result<Automaton<S>, AutomatonError<S>> {
+        let alphabet = alphabet.into();
+        let size = accepting.len();
+        if transitions.len() != size {
+            return Err(AutomatonError::SizeMismatch {
+                accepting: size,
+                transitions: transitions.len(),
+            });
+        }
+        check_state(initial, size)?;
+        for transition in transitions.iter().flatten() {
+            check_state(transition.next_state, size)?;
+            check_symbol(&alphabet, &transition.symbol)?;
+        }
+        Ok(Automaton::from(alphabet, initial, accepting, transitions))
+    }
+
+    pub fn try_set_size(&mut self, new_size: usize) -> Result<(), AutomatonError<S>> {
+        if new_size < self.size() {
+            return Err(AutomatonError::SizeDecrease {
+                size: self.size(),
+                new_size,
+            });
+        }
+        self.set_size(new_size);
+        Ok(())
+    }
+
+    pub fn try_set_initial(&mut self, new_initial: usize) -> Result<(), AutomatonError<S>> {
+        check_state(new_initial, self.size())?;
+        self.set_initial(new_initial);
+        Ok(())
+    }
+
+    pub fn try_set_accepting(
+        &mut self,
+        state: usize,
+        new_accepting: bool,
+    ) -> Result<(), AutomatonError<S>> {
+        check_state(state, self.size())?;
+        self.set_accepting(state, new_accepting);
+        Ok(())
+    }
+
+    pub fn try_add_empty_transition(
+        &mut self,
+        from: usize,
+        to: usize,
+    ) -> Result<(), AutomatonError<S>> {
+        self.try_add_transition(from, to, None)
+    }
+
+    pub fn try_add_symbol_transition(
+        &mut self,
+        from: usize,
+        to: usize,
+        symbol: S,
+    ) -> Result<(), AutomatonError<S>> {
+        self.try_add_transition(from, to, Some(symbol))
+    }
+
+    pub fn try_add_transition(
+        &mut self,
+        from: usize,
+        to: usize,
+        symbol: Option<S>,
+    ) -> Result<(), AutomatonError<S>> {
+        check_state(from, self.size())?;
+        check_state(to, self.size())?;
+        check_symbol(self.alphabet(), &symbol)?;
+        self.add_transition(from, to, symbol);
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use automaton::*;
 use automaton::char_automaton::*;
 
 fn main() {
-    let automaton = Automaton::from_regex(&['a', 'b'], &parse_regex_from_string("a*b|ababa"));
+    let automaton = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("a*b|ababa").unwrap());
     assert!(accepted_str(&automaton, "ababa"));
     assert!(accepted_str(&automaton, "aaaab"));
     assert!(accepted_str(&automaton, "b"));
@@ -3,9 +3,9 @@ use automaton::char_automaton::*;
 
 fn main() {
     let automaton = automaton_from_string(
-        &['a', 'b'],
+        ['a', 'b'],
         "0\n1\n0 0 a\n0 1\n1 1 b"
-    );
+    ).unwrap();
     assert!(accepted_str(&automaton, "aabb"));
     assert!(!accepted_str(&automaton, "aba"));
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ use automaton::char_automaton::*;
 
 fn main() {
     let nfa = Automaton::from(
-        &['0', '1'],
+        ['0', '1'],
         0,
         vec![false, false, false, false, true],
         vec![
@@ -17,4 +17,6 @@ fn main() {
     let dfa = Automaton::dfa_from(&nfa);
     println!("NFA GraphViz:\n{}", automaton_to_gviz_dot(&nfa));
     println!("DFA GraphViz:\n{}", automaton_to_gviz_dot(&dfa));
+    let (trim_dfa, _) = Automaton::trim_of(&dfa);
+    println!("Trim DFA GraphViz:\n{}", automaton_to_gviz_dot(&trim_dfa));
 }
\ No newline at end of file
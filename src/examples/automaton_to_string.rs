@@ -2,7 +2,7 @@ use automaton::*;
 use automaton::char_automaton::*;
 
 fn main() {
-    let mut automaton = Automaton::new(&['α', 'β', 'γ'], 3);
+    let mut automaton = Automaton::new(['α', 'β', 'γ'], 3);
     automaton.set_initial(0);
     automaton.set_accepting(2, true);
     automaton.add_symbol_transition(0, 1, 'α');
@@ -0,0 +1,395 @@
+use super::*;
+use num_bigint::RandBigInt;
+use rand::Rng;
+
+pub type BigCount = num_bigint::BigUint;
+
+fn is_sorted_subset(lhs: &[usize], rhs: &[usize]) -> bool {
+    let mut rhs_iter = rhs.iter();
+    lhs.iter().all(|x| rhs_iter.any(|y| y == x))
+}
+
+impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
+    // Breadth-first search over symbol transitions in alphabet order, so the first
+    // accepting state found is reached by the shortlex-least word.
+    fn shortest_word_to_accepting(&self) -> Option<Vec<S>> {
+        let mut parent: Vec<Option<(usize, S)>> = vec![None; self.size()];
+        let mut visited = vec![false; self.size()];
+        let mut queue = VecDeque::from([self.initial()]);
+        visited[self.initial()] = true;
+        while let Some(state) = queue.pop_front() {
+            if self.accepting(state) {
+                let mut word = vec![];
+                let mut current_state = state;
+                while let Some((previous_state, symbol)) = parent[current_state].clone() {
+                    word.push(symbol);
+                    current_state = previous_state;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for c in self.alphabet() {
+                for next_state in self.symbol_transitions(state, c) {
+                    if !visited[*next_state] {
+                        visited[*next_state] = true;
+                        parent[*next_state] = Some((state, c.clone()));
+                        queue.push_back(*next_state);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn shortest_accepted(&self) -> Option<Vec<S>> {
+        Automaton::single_symbol_nfa_from(self).shortest_word_to_accepting()
+    }
+
+    pub fn shortest_rejected(&self) -> Option<Vec<S>> {
+        Automaton::complement_of(self).shortest_word_to_accepting()
+    }
+
+    pub fn equivalent(lhs: &Automaton<S>, rhs: &Automaton<S>) -> Result<(), Vec<S>> {
+        let difference =
+            Automaton::product_dfa_from(lhs, rhs, ProductOperation::SymmetricDifference);
+        match difference.shortest_word_to_accepting() {
+            Some(word) => Err(word),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_subset_of(&self, other: &Automaton<S>) -> Result<(), Vec<S>> {
+        Automaton::check_same_alphabet(self, other);
+        let lhs = Automaton::single_symbol_nfa_from(self);
+        let rhs = Automaton::single_symbol_nfa_from(other);
+        // Each node pairs a state of `lhs` with the set of `rhs` states reached by the
+        // same word. A node is subsumed by a visited one with the same `lhs` state and
+        // a subset of its `rhs` states, so only the antichain of minimal sets is kept.
+        let mut nodes = vec![(lhs.initial(), vec![rhs.initial()])];
+        let mut parent: Vec<Option<(usize, S)>> = vec![None];
+        let mut antichains: Vec<Vec<Vec<usize>>> = vec![vec![]; lhs.size()];
+        antichains[lhs.initial()].push(vec![rhs.initial()]);
+        let mut queue = VecDeque::from([0]);
+        while let Some(index) = queue.pop_front() {
+            let (state, states) = nodes[index].clone();
+            if lhs.accepting(state) && !states.iter().any(|state| rhs.accepting(*state)) {
+                let mut word = vec![];
+                let mut current_index = index;
+                while let Some((previous_index, symbol)) = parent[current_index].clone() {
+                    word.push(symbol);
+                    current_index = previous_index;
+                }
+                word.reverse();
+                return Err(word);
+            }
+            for c in lhs.alphabet() {
+                let mut next_states: Vec<usize> = states
+                    .iter()
+                    .flat_map(|state| rhs.symbol_transitions(*state, c))
+                    .cloned()
+                    .collect();
+                next_states.sort();
+                next_states.dedup();
+                for next_state in lhs.symbol_transitions(state, c) {
+                    let antichain = &mut antichains[*next_state];
+                    if antichain
+                        .iter()
+                        .any(|visited| is_sorted_subset(visited, &next_states))
+                    {
+                        continue;
+                    }
+                    antichain.retain(|visited| !is_sorted_subset(&next_states, visited));
+                    antichain.push(next_states.clone());
+                    nodes.push((*next_state, next_states.clone()));
+                    parent.push(Some((index, c.clone())));
+                    queue.push_back(nodes.len() - 1);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Topological order of the states lying on some accepting path,
+    // or `None` if they form a cycle.
+    fn useful_states_order(&self) -> Option<Vec<usize>> {
+        let co_reached = self.co_reached();
+        let mut useful = vec![false; self.size()];
+        self.reached(self.initial())
+            .into_iter()
+            .for_each(|state| useful[state] = co_reached[state]);
+        let mut in_degree = vec![0; self.size()];
+        for state in (0..self.size()).filter(|state| useful[*state]) {
+            for Transition { next_state, .. } in self.transitions(state) {
+                if useful[next_state] {
+                    in_degree[next_state] += 1;
+                }
+            }
+        }
+        let mut order: Vec<usize> = (0..self.size())
+            .filter(|state| useful[*state] && in_degree[*state] == 0)
+            .collect();
+        let mut index = 0;
+        while index < order.len() {
+            for Transition { next_state, .. } in self.transitions(order[index]) {
+                if useful[next_state] {
+                    in_degree[next_state] -= 1;
+                    if in_degree[next_state] == 0 {
+                        order.push(next_state);
+                    }
+                }
+            }
+            index += 1;
+        }
+        if order.len() == useful.iter().filter(|x| **x).count() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self
+            .reached(self.initial())
+            .into_iter()
+            .any(|state| self.accepting(state))
+    }
+
+    pub fn is_universal(&self) -> bool {
+        Automaton::complement_of(self).is_empty()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        Automaton::single_symbol_nfa_from(self)
+            .useful_states_order()
+            .is_some()
+    }
+
+    pub fn language_size(&self) -> Option<BigCount> {
+        let dfa = Automaton::dfa_from(self);
+        let order = dfa.useful_states_order()?;
+        let mut counts = vec![BigCount::default(); dfa.size()];
+        for state in order.into_iter().rev() {
+            let mut count = BigCount::from(dfa.accepting(state) as u8);
+            for Transition { next_state, .. } in dfa.transitions(state) {
+                count += &counts[next_state];
+            }
+            counts[state] = count;
+        }
+        Some(counts[dfa.initial()].clone())
+    }
+
+    // Number of accepted words of every length from 0 to `max_len`, obtained by
+    // propagating path counts through the DFA one symbol at a time.
+    fn word_counts(&self, max_len: usize) -> Vec<BigCount> {
+        let dfa = Automaton::dfa_from(self);
+        let mut paths = vec![BigCount::default(); dfa.size()];
+        paths[dfa.initial()] = BigCount::from(1u8);
+        let mut counts = vec![];
+        for len in 0..=max_len {
+            counts.push(
+                (0..dfa.size())
+                    .filter(|state| dfa.accepting(*state))
+                    .map(|state| &paths[state])
+                    .sum(),
+            );
+            if len == max_len {
+                break;
+            }
+            let mut next_paths = vec![BigCount::default(); dfa.size()];
+            for (state, state_paths) in paths.iter().enumerate() {
+                for Transition { next_state, .. } in dfa.transitions(state) {
+                    next_paths[next_state] += state_paths;
+                }
+            }
+            paths = next_paths;
+        }
+        counts
+    }
+
+    pub fn count_words_big(&self, len: usize) -> BigCount {
+        self.word_counts(len).pop().expect("counts are non-empty")
+    }
+
+    pub fn count_words_up_to_big(&self, max_len: usize) -> BigCount {
+        self.word_counts(max_len).into_iter().sum()
+    }
+
+    // None if the count does not fit into u128.
+    pub fn count_words(&self, len: usize) -> Option<u128> {
+        u128::try_from(self.count_words_big(len)).ok()
+    }
+
+    pub fn count_words_up_to(&self, max_len: usize) -> Option<u128> {
+        u128::try_from(self.count_words_up_to_big(max_len)).ok()
+    }
+
+    pub fn sample_word<R: Rng + ?Sized>(&self, len: usize, rng: &mut R) -> Option<Vec<S>> {
+        let mcdfa = Automaton::minimal_complete_dfa_from(self);
+        // accepted_in[steps][state] is the number of words of length `steps`
+        // leading from `state` to an accepting state.
+        let mut accepted_in: Vec<Vec<BigCount>> = vec![(0..mcdfa.size())
+            .map(|state| BigCount::from(mcdfa.accepting(state) as u8))
+            .collect()];
+        for steps in 1..=len {
+            let next = (0..mcdfa.size())
+                .map(|state| {
+                    mcdfa
+                        .transitions(state)
+                        .iter()
+                        .map(|transition| &accepted_in[steps - 1][transition.next_state])
+                        .sum()
+                })
+                .collect();
+            accepted_in.push(next);
+        }
+        let total = &accepted_in[len][mcdfa.initial()];
+        if *total == BigCount::default() {
+            return None;
+        }
+        // Pick the index of the word among all accepted words of length `len`
+        // in lexicographic order, then descend symbol by symbol to find it.
+        let mut index = rng.gen_biguint_below(total);
+        let mut state = mcdfa.initial();
+        let mut word = Vec::with_capacity(len);
+        for steps in (0..len).rev() {
+            for c in mcdfa.alphabet() {
+                let next_state = mcdfa.symbol_transitions(state, c)[0];
+                let count = &accepted_in[steps][next_state];
+                if index < *count {
+                    word.push(c.clone());
+                    state = next_state;
+                    break;
+                }
+                index -= count;
+            }
+        }
+        Some(word)
+    }
+
+    fn words_of_lengths(&self, min_len: usize, max_len: Option<usize>) -> Words<S> {
+        let dfa = Automaton::dfa_from(self);
+        // `None` for an infinite language and `Some(None)` for the empty one.
+        let longest = dfa.useful_states_order().map(|order| {
+            let mut longest_from = vec![None; dfa.size()];
+            for state in order.into_iter().rev() {
+                longest_from[state] = dfa
+                    .transitions(state)
+                    .into_iter()
+                    .filter_map(|Transition { next_state, .. }| longest_from[next_state])
+                    .map(|len: usize| len + 1)
+                    .max()
+                    .or(dfa.accepting(state).then_some(0));
+            }
+            longest_from[dfa.initial()]
+        });
+        let exhausted = longest == Some(None);
+        let max_len = match (max_len, longest.flatten()) {
+            (Some(max_len), Some(longest)) => Some(max_len.min(longest)),
+            (max_len, longest) => max_len.or(longest),
+        };
+        Words {
+            accepting_in: vec![(0..dfa.size()).map(|state| dfa.accepting(state)).collect()],
+            dfa,
+            len: min_len,
+            max_len,
+            exhausted,
+            stack: vec![],
+            word: vec![],
+        }
+    }
+
+    pub fn words(&self) -> Words<S> {
+        self.words_of_lengths(0, None)
+    }
+
+    pub fn words_of_length(&self, len: usize) -> Words<S> {
+        self.words_of_lengths(len, Some(len))
+    }
+
+    pub fn words_up_to(&self, max_len: usize) -> Words<S> {
+        self.words_of_lengths(0, Some(max_len))
+    }
+}
+
+// Yields accepted words in shortlex order by a depth-first search over the DFA
+// for each length, descending only into states that still reach acceptance
+// in the remaining number of steps.
+pub struct Words<S: Eq + Clone + Hash + 'static> {
+    dfa: Automaton<S>,
+    accepting_in: Vec<Vec<bool>>,
+    len: usize,
+    max_len: Option<usize>,
+    exhausted: bool,
+    stack: Vec<(usize, usize)>,
+    word: Vec<S>,
+}
+
+impl<S: Eq + Clone + Hash + 'static> Words<S> {
+    fn accepting_in(&mut self, steps: usize, state: usize) -> bool {
+        while self.accepting_in.len() <= steps {
+            let last = self.accepting_in.last().expect("table is non-empty");
+            let next = (0..self.dfa.size())
+                .map(|state| {
+                    self.dfa
+                        .transitions(state)
+                        .iter()
+                        .any(|transition| last[transition.next_state])
+                })
+                .collect();
+            self.accepting_in.push(next);
+        }
+        self.accepting_in[steps][state]
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> Iterator for Words<S> {
+    type Item = Vec<S>;
+
+    fn next(&mut self) -> Option<Vec<S>> {
+        loop {
+            if self.exhausted {
+                return None;
+            }
+            if self.stack.is_empty() {
+                if self.max_len.is_some_and(|max_len| self.len > max_len) {
+                    self.exhausted = true;
+                    continue;
+                }
+                let len = self.len;
+                self.len += 1;
+                if !self.accepting_in(len, self.dfa.initial()) {
+                    continue;
+                }
+                if len == 0 {
+                    return Some(vec![]);
+                }
+                self.stack.push((self.dfa.initial(), 0));
+                continue;
+            }
+            let len = self.len - 1;
+            let depth = self.stack.len() - 1;
+            let (state, index) = self.stack[depth];
+            if index == self.dfa.alphabet().len() {
+                self.stack.pop();
+                self.word.pop();
+                continue;
+            }
+            self.stack[depth].1 += 1;
+            let c = self.dfa.alphabet()[index].clone();
+            let next_state = match self.dfa.symbol_transitions(state, &c).first() {
+                Some(next_state) => *next_state,
+                None => continue,
+            };
+            if !self.accepting_in(len - depth - 1, next_state) {
+                continue;
+            }
+            if depth + 1 == len {
+                let mut word = self.word.clone();
+                word.push(c);
+                return Some(word);
+            }
+            self.word.push(c);
+            self.stack.push((next_state, 0));
+        }
+    }
+}
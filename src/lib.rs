@@ -1,78 +1,259 @@
 use smallvec::{smallvec, SmallVec};
+use state_set::StateSet;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::fmt::Formatter;
 use std::hash::Hash;
+use std::ops::Deref;
+use std::sync::Arc;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Regex<S: Eq + Clone + Hash + 'static> {
+    // The empty language.
+    Empty,
+    // The language of the empty word only, also written as an empty `String`.
+    #[default]
+    Epsilon,
     String(Vec<S>),
+    Class(Vec<S>),
+    NegatedClass(Vec<S>),
     Concat(Box<Regex<S>>, Box<Regex<S>>),
     Union(Box<Regex<S>>, Box<Regex<S>>),
+    Intersection(Box<Regex<S>>, Box<Regex<S>>),
+    // Words over the alphabet that the inner regex does not match.
+    Complement(Box<Regex<S>>),
     KleeneStar(Box<Regex<S>>),
 }
 
 impl<S: Eq + Clone + Hash + 'static> Regex<S> {
+    pub fn is_epsilon(&self) -> bool {
+        matches!(self, Regex::Epsilon) || matches!(self, Regex::String(vec) if vec.is_empty())
+    }
+
+    pub fn is_empty_language(&self) -> bool {
+        matches!(self, Regex::Empty) || matches!(self, Regex::Class(symbols) if symbols.is_empty())
+    }
+
     pub fn concat(lhs: Regex<S>, rhs: Regex<S>) -> Regex<S> {
         use Regex::*;
-        if let String(lhs_vec) = &lhs {
-            if lhs_vec.is_empty() {
-                return rhs;
-            }
+        if lhs.is_empty_language() || rhs.is_empty_language() {
+            return Empty;
         }
-        if let String(rhs_vec) = &rhs {
-            if rhs_vec.is_empty() {
-                return lhs;
-            }
+        if lhs.is_epsilon() {
+            return rhs;
+        }
+        if rhs.is_epsilon() {
+            return lhs;
         }
-        if let String(lhs_vec) = &lhs {
-            if let String(rhs_vec) = &rhs {
-                return String(vec![lhs_vec.clone(), rhs_vec.clone()].concat());
+        match (lhs, rhs) {
+            (String(mut lhs_vec), String(rhs_vec)) => {
+                lhs_vec.extend(rhs_vec);
+                String(lhs_vec)
             }
+            // Concatenations are kept nested to the left with adjacent strings merged,
+            // which is the tree `parse_regex_from_string` builds.
+            (Concat(lhs, middle), String(rhs_vec)) if matches!(*middle, String(_)) => {
+                Concat(lhs, Box::new(Regex::concat(*middle, String(rhs_vec))))
+            }
+            (lhs, Concat(middle, rhs)) => Regex::concat(Regex::concat(lhs, *middle), *rhs),
+            (lhs, rhs) => Concat(Box::new(lhs), Box::new(rhs)),
         }
-        Concat(Box::new(lhs), Box::new(rhs))
     }
 
     pub fn union(lhs: Regex<S>, rhs: Regex<S>) -> Regex<S> {
-        Regex::Union(Box::new(lhs), Box::new(rhs))
+        if lhs.is_empty_language() {
+            return rhs;
+        }
+        if rhs.is_empty_language() {
+            return lhs;
+        }
+        match rhs {
+            Regex::Union(middle, rhs) => Regex::union(Regex::union(lhs, *middle), *rhs),
+            rhs => Regex::Union(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    pub fn intersection(lhs: Regex<S>, rhs: Regex<S>) -> Regex<S> {
+        if lhs.is_empty_language() || rhs.is_empty_language() {
+            return Regex::Empty;
+        }
+        match rhs {
+            Regex::Intersection(middle, rhs) => {
+                Regex::intersection(Regex::intersection(lhs, *middle), *rhs)
+            }
+            rhs => Regex::Intersection(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    pub fn complement(regex: Regex<S>) -> Regex<S> {
+        Regex::Complement(Box::new(regex))
     }
 
     pub fn kleene_star(regex: Regex<S>) -> Regex<S> {
-        use Regex::*;
-        if let String(vec) = &regex {
-            if vec.is_empty() {
-                return String(vec![]);
+        if regex.is_epsilon() || regex.is_empty_language() {
+            return Regex::Epsilon;
+        }
+        Regex::KleeneStar(Box::new(regex))
+    }
+
+    pub fn plus(regex: Regex<S>) -> Regex<S> {
+        Regex::concat(regex.clone(), Regex::kleene_star(regex))
+    }
+
+    pub fn optional(regex: Regex<S>) -> Regex<S> {
+        Regex::union(regex, Regex::Epsilon)
+    }
+
+    pub fn repeat(regex: Regex<S>, min: usize, max: Option<usize>) -> Regex<S> {
+        let mut result = Regex::Epsilon;
+        for _ in 0..min {
+            result = Regex::concat(result, regex.clone());
+        }
+        match max {
+            None => Regex::concat(result, Regex::kleene_star(regex)),
+            Some(max) => {
+                if max < min {
+                    panic!("invalid repetition bounds");
+                }
+                // r{m,n} is the flat chain r^m (r?)^(n-m), so its size grows linearly with n.
+                for _ in min..max {
+                    result = Regex::concat(result, Regex::optional(regex.clone()));
+                }
+                result
             }
         }
-        KleeneStar(Box::new(regex))
     }
 }
 
-impl<S: Eq + Clone + Hash + 'static> Default for Regex<S> {
-    fn default() -> Self {
-        Regex::String(vec![])
+// Symbols that have to be escaped to be read literally by `parse_regex_from_string`,
+// together with whitespace, which the parser skips next to some operators.
+const REGEX_METACHARACTERS: &str = "()|&~*+?{}[].\\ε∅";
+const CLASS_METACHARACTERS: &str = "\\]-^";
+
+fn write_regex_symbol<S: fmt::Display>(
+    f: &mut Formatter,
+    symbol: &S,
+    metacharacters: &str,
+) -> fmt::Result {
+    let symbol = symbol.to_string();
+    let mut chars = symbol.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if metacharacters.contains(c) || c.is_whitespace() {
+            f.write_str("\\")?;
+        }
     }
+    f.write_str(&symbol)
 }
 
+impl<S: Eq + Clone + Hash + 'static + fmt::Display> Regex<S> {
+    // From the weakest binding: union, intersection, concatenation, complement, star.
+    fn precedence(&self) -> u8 {
+        match self {
+            Regex::Union(..) => 0,
+            Regex::Intersection(..) => 1,
+            Regex::Concat(..) => 2,
+            Regex::String(vec) if vec.len() > 1 => 2,
+            Regex::Complement(_) => 3,
+            Regex::KleeneStar(_) => 4,
+            _ => 5,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut Formatter, min_precedence: u8) -> fmt::Result {
+        if self.precedence() < min_precedence {
+            f.write_fmt(format_args!("({})", self))
+        } else {
+            f.write_fmt(format_args!("{}", self))
+        }
+    }
+
+    // Runs of three or more consecutive chars are written as ranges.
+    fn fmt_class(f: &mut Formatter, prefix: &str, symbols: &[S]) -> fmt::Result {
+        let chars: Vec<Option<char>> = symbols
+            .iter()
+            .map(|symbol| {
+                let symbol = symbol.to_string();
+                let mut chars = symbol.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => None,
+                }
+            })
+            .collect();
+        let follows = |index: usize| {
+            chars[index]
+                .and_then(|c| char::from_u32(c as u32 + 1))
+                .is_some_and(|next| chars[index + 1] == Some(next))
+        };
+        f.write_str(prefix)?;
+        let mut start = 0;
+        while start < symbols.len() {
+            let mut end = start;
+            while end + 1 < symbols.len() && follows(end) {
+                end += 1;
+            }
+            if end - start >= 2 {
+                write_regex_symbol(f, &symbols[start], CLASS_METACHARACTERS)?;
+                f.write_str("-")?;
+                write_regex_symbol(f, &symbols[end], CLASS_METACHARACTERS)?;
+            } else {
+                for c in &symbols[start..=end] {
+                    write_regex_symbol(f, c, CLASS_METACHARACTERS)?;
+                }
+            }
+            start = end + 1;
+        }
+        f.write_str("]")
+    }
+}
+
+// The parser reads concatenations and unions left to right, so a right operand of
+// the same operator keeps its parentheses and the output parses back to the same tree.
+// A string on the right needs none, since `concat` merges it with the symbols before it.
 impl<S: Eq + Clone + Hash + 'static + fmt::Display> fmt::Display for Regex<S> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
+            Regex::Empty => f.write_str("∅"),
+            Regex::Epsilon => f.write_str("ε"),
             Regex::String(vec) => {
+                if vec.is_empty() {
+                    f.write_str("ε")?;
+                }
                 for c in vec {
-                    f.write_fmt(format_args!("({})", c))?;
+                    write_regex_symbol(f, c, REGEX_METACHARACTERS)?;
                 }
+                Ok(())
             }
+            Regex::Class(symbols) if symbols.is_empty() => f.write_str("∅"),
+            Regex::Class(symbols) => Regex::fmt_class(f, "[", symbols),
+            Regex::NegatedClass(symbols) if symbols.is_empty() => f.write_str("."),
+            Regex::NegatedClass(symbols) => Regex::fmt_class(f, "[^", symbols),
             Regex::Concat(lhs, rhs) => {
-                f.write_fmt(format_args!("({}{})", lhs, rhs))?;
+                lhs.fmt_operand(f, 2)?;
+                match **rhs {
+                    Regex::String(_) => rhs.fmt_operand(f, 2),
+                    _ => rhs.fmt_operand(f, 3),
+                }
             }
             Regex::Union(lhs, rhs) => {
-                f.write_fmt(format_args!("({}|{})", lhs, rhs))?;
+                lhs.fmt_operand(f, 0)?;
+                f.write_str("|")?;
+                rhs.fmt_operand(f, 1)
+            }
+            Regex::Intersection(lhs, rhs) => {
+                lhs.fmt_operand(f, 1)?;
+                f.write_str("&")?;
+                rhs.fmt_operand(f, 2)
+            }
+            Regex::Complement(regex) => {
+                f.write_str("~")?;
+                regex.fmt_operand(f, 3)
             }
             Regex::KleeneStar(regex) => {
-                f.write_fmt(format_args!("({}*)", regex))?;
+                regex.fmt_operand(f, 4)?;
+                f.write_str("*")
             }
         }
-        Ok(())
     }
 }
 
@@ -98,9 +279,79 @@ impl<S: Eq + Clone + Hash + 'static> Transition<S> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet<S: Eq + Clone + Hash + 'static> {
+    symbols: Arc<[S]>,
+}
+
+impl<S: Eq + Clone + Hash + 'static> Alphabet<S> {
+    pub fn new(symbols: Vec<S>) -> Alphabet<S> {
+        Alphabet {
+            symbols: symbols.into(),
+        }
+    }
+
+    pub fn symbols(&self) -> &[S] {
+        &self.symbols
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> Deref for Alphabet<S> {
+    type Target = [S];
+
+    fn deref(&self) -> &[S] {
+        &self.symbols
+    }
+}
+
+impl<'a, S: Eq + Clone + Hash + 'static> IntoIterator for &'a Alphabet<S> {
+    type Item = &'a S;
+    type IntoIter = std::slice::Iter<'a, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.symbols.iter()
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> From<Arc<[S]>> for Alphabet<S> {
+    fn from(symbols: Arc<[S]>) -> Self {
+        Alphabet { symbols }
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> From<Vec<S>> for Alphabet<S> {
+    fn from(symbols: Vec<S>) -> Self {
+        Alphabet::new(symbols)
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> From<&[S]> for Alphabet<S> {
+    fn from(symbols: &[S]) -> Self {
+        Alphabet::new(symbols.to_vec())
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static, const N: usize> From<[S; N]> for Alphabet<S> {
+    fn from(symbols: [S; N]) -> Self {
+        Alphabet::new(symbols.into())
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static, const N: usize> From<&[S; N]> for Alphabet<S> {
+    fn from(symbols: &[S; N]) -> Self {
+        Alphabet::new(symbols.to_vec())
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> From<&Alphabet<S>> for Alphabet<S> {
+    fn from(alphabet: &Alphabet<S>) -> Self {
+        alphabet.clone()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Automaton<S: Eq + Clone + Hash + 'static> {
-    alphabet: &'static [S],
+    alphabet: Alphabet<S>,
     size: usize,
     initial: usize,
     accepting: Vec<bool>,
@@ -108,12 +359,12 @@ pub struct Automaton<S: Eq + Clone + Hash + 'static> {
 }
 
 impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
-    pub fn new(alphabet: &'static [S], size: usize) -> Automaton<S> {
+    pub fn new(alphabet: impl Into<Alphabet<S>>, size: usize) -> Automaton<S> {
         Automaton::from(alphabet, 0, vec![false; size], vec![vec![]; size])
     }
 
     pub fn from(
-        alphabet: &'static [S],
+        alphabet: impl Into<Alphabet<S>>,
         initial: usize,
         accepting: Vec<bool>,
         transitions: Vec<Vec<Transition<S>>>,
@@ -134,7 +385,7 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
             }
         }
         Automaton {
-            alphabet,
+            alphabet: alphabet.into(),
             size,
             initial,
             accepting,
@@ -160,8 +411,8 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
         self.size
     }
 
-    pub fn alphabet(&self) -> &'static [S] {
-        self.alphabet
+    pub fn alphabet(&self) -> &Alphabet<S> {
+        &self.alphabet
     }
 
     pub fn initial(&self) -> usize {
@@ -173,19 +424,16 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
     }
 
     pub fn symbol_transitions(&self, state: usize, symbol: &S) -> &[usize] {
-        let key = Some(symbol.clone());
-        if self.transitions[state].contains_key(&key) {
-            &self.transitions[state][&key][..]
-        } else {
-            &[]
+        match self.transitions[state].get(&Some(symbol.clone())) {
+            Some(next_states) => &next_states[..],
+            None => &[],
         }
     }
 
     pub fn empty_transitions(&self, state: usize) -> &[usize] {
-        if self.transitions[state].contains_key(&None) {
-            &self.transitions[state][&None][..]
-        } else {
-            &[]
+        match self.transitions[state].get(&None) {
+            Some(next_states) => &next_states[..],
+            None => &[],
         }
     }
 
@@ -237,23 +485,28 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
     }
 
     pub fn transitions(&self, state: usize) -> Vec<Transition<S>> {
-        vec![
+        [
             self.alphabet()
                 .iter()
-                .map(|c| {
+                .flat_map(|c| {
                     self.symbol_transitions(state, c)
-                        .into_iter()
+                        .iter()
                         .map(|next_state| Transition::single_symbol(c.clone(), *next_state))
                         .collect::<Vec<_>>()
                 })
-                .flatten()
                 .collect::<Vec<_>>(),
             self.empty_transitions(state)
-                .into_iter()
+                .iter()
                 .map(|next_state| Transition::empty(*next_state))
                 .collect(),
         ]
-            .concat()
+        .concat()
+    }
+
+    fn check_same_alphabet(lhs: &Automaton<S>, rhs: &Automaton<S>) {
+        if lhs.alphabet() != rhs.alphabet() {
+            panic!("alphabet mismatch");
+        }
     }
 
     fn reached_by_epsilon(&self, state: usize) -> SmallVec<[usize; 1]> {
@@ -262,19 +515,15 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
         } else {
             let mut visited: HashSet<usize> = HashSet::from([state]);
             let mut queue: VecDeque<usize> = VecDeque::from([state]);
-            loop {
-                if let Some(current_state) = queue.pop_front() {
-                    self.empty_transitions(current_state)
-                        .iter()
-                        .for_each(|next_state| {
-                            if !visited.contains(next_state) {
-                                visited.insert(*next_state);
-                                queue.push_back(*next_state);
-                            }
-                        })
-                } else {
-                    break;
-                }
+            while let Some(current_state) = queue.pop_front() {
+                self.empty_transitions(current_state)
+                    .iter()
+                    .for_each(|next_state| {
+                        if !visited.contains(next_state) {
+                            visited.insert(*next_state);
+                            queue.push_back(*next_state);
+                        }
+                    })
             }
             visited.into_iter().collect()
         }
@@ -283,43 +532,82 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
     fn reached(&self, state: usize) -> Vec<usize> {
         let mut visited: HashSet<usize> = HashSet::from([state]);
         let mut queue: VecDeque<usize> = VecDeque::from([state]);
-        loop {
-            if let Some(current_state) = queue.pop_front() {
-                self.transitions(current_state).iter().for_each(
-                    |Transition { next_state, .. }| {
-                        if !visited.contains(next_state) {
-                            visited.insert(*next_state);
-                            queue.push_back(*next_state);
-                        }
-                    },
-                )
-            } else {
-                break;
-            }
+        while let Some(current_state) = queue.pop_front() {
+            self.transitions(current_state)
+                .iter()
+                .for_each(|Transition { next_state, .. }| {
+                    if !visited.contains(next_state) {
+                        visited.insert(*next_state);
+                        queue.push_back(*next_state);
+                    }
+                })
         }
         visited.into_iter().collect()
     }
 
+    fn co_reached(&self) -> Vec<bool> {
+        let mut reverse_transitions = vec![vec![]; self.size()];
+        for state in 0..self.size() {
+            for Transition { next_state, .. } in self.transitions(state) {
+                reverse_transitions[next_state].push(state);
+            }
+        }
+        let mut visited: Vec<bool> = (0..self.size())
+            .map(|state| self.accepting(state))
+            .collect();
+        let mut queue: VecDeque<usize> = (0..self.size()).filter(|state| visited[*state]).collect();
+        while let Some(current_state) = queue.pop_front() {
+            reverse_transitions[current_state]
+                .iter()
+                .for_each(|previous_state| {
+                    if !visited[*previous_state] {
+                        visited[*previous_state] = true;
+                        queue.push_back(*previous_state);
+                    }
+                })
+        }
+        visited
+    }
+
+    fn close_by_epsilon(&self, states: &mut StateSet) {
+        let mut index = 0;
+        while index < states.states().len() {
+            let state = states.states()[index];
+            self.empty_transitions(state).iter().for_each(|next_state| {
+                states.insert(*next_state);
+            });
+            index += 1;
+        }
+    }
+
+    fn step(&self, states: &StateSet, symbol: &S, next_states: &mut StateSet) {
+        next_states.clear();
+        for state in states.states() {
+            self.symbol_transitions(*state, symbol)
+                .iter()
+                .for_each(|next_state| {
+                    next_states.insert(*next_state);
+                });
+        }
+        self.close_by_epsilon(next_states);
+    }
+
     pub fn accepted_from_state(&self, state: usize, word: &[S]) -> bool {
-        if word.is_empty() {
-            self.reached_by_epsilon(state)
-                .into_iter()
-                .any(|reached_state| self.accepting(reached_state))
-        } else {
-            if self.empty_transitions(state).is_empty() {
-                self.symbol_transitions(state, &word[0])
-                    .iter()
-                    .any(|next_state| self.accepted_from_state(*next_state, &word[1..]))
-            } else {
-                self.reached_by_epsilon(state)
-                    .into_iter()
-                    .map(|reached_state| self.symbol_transitions(reached_state, &word[0]))
-                    .flatten()
-                    .collect::<HashSet<_>>()
-                    .into_iter()
-                    .any(|next_state| self.accepted_from_state(*next_state, &word[1..]))
+        let mut states = StateSet::new(self.size());
+        let mut next_states = StateSet::new(self.size());
+        states.insert(state);
+        self.close_by_epsilon(&mut states);
+        for symbol in word {
+            if states.is_empty() {
+                return false;
             }
+            self.step(&states, symbol, &mut next_states);
+            std::mem::swap(&mut states, &mut next_states);
         }
+        states
+            .states()
+            .iter()
+            .any(|reached_state| self.accepting(*reached_state))
     }
 
     pub fn accepted(&self, word: &[S]) -> bool {
@@ -327,27 +615,25 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
     }
 
     pub fn is_single_symbol(&self) -> bool {
-        (0..self.size())
-            .into_iter()
-            .all(|state| self.empty_transitions(state).is_empty())
+        (0..self.size()).all(|state| self.empty_transitions(state).is_empty())
     }
 
     pub fn is_dfa(&self) -> bool {
         self.is_single_symbol()
             && (0..self.size()).into_iter().all(|state| {
-            self.alphabet()
-                .iter()
-                .all(|c| self.symbol_transitions(state, c).len() <= 1)
-        })
+                self.alphabet()
+                    .iter()
+                    .all(|c| self.symbol_transitions(state, c).len() <= 1)
+            })
     }
 
     pub fn is_complete_dfa(&self) -> bool {
         self.is_single_symbol()
             && (0..self.size()).into_iter().all(|state| {
-            self.alphabet()
-                .iter()
-                .all(|c| self.symbol_transitions(state, c).len() == 1)
-        })
+                self.alphabet()
+                    .iter()
+                    .all(|c| self.symbol_transitions(state, c).len() == 1)
+            })
     }
 
     pub fn single_symbol_nfa_from(automaton: &Automaton<S>) -> Automaton<S> {
@@ -355,7 +641,6 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
             return automaton.clone();
         }
         let accepting = (0..automaton.size())
-            .into_iter()
             .map(|state| {
                 automaton
                     .reached_by_epsilon(state)
@@ -364,19 +649,17 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
             })
             .collect();
         let transitions = (0..automaton.size())
-            .into_iter()
             .map(|state| {
                 automaton
                     .reached_by_epsilon(state)
                     .into_iter()
-                    .map(|reached_state| {
+                    .flat_map(|reached_state| {
                         automaton
                             .transitions(reached_state)
                             .into_iter()
                             .filter(|transition| transition.symbol.is_some())
                             .collect::<Vec<_>>()
                     })
-                    .flatten()
                     .collect::<Vec<_>>()
             })
             .collect();
@@ -392,14 +675,31 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
         if automaton.is_dfa() {
             return automaton.clone();
         }
-        let ss_nfa = Automaton::single_symbol_nfa_from(automaton);
+        // Subsets are epsilon-closed and keep only accepting states and states with
+        // symbol transitions. Other states never affect the language, and keeping them
+        // would split subsets that differ only in such states into distinct states.
+        let closures: Vec<Vec<usize>> = (0..automaton.size())
+            .map(|state| {
+                automaton
+                    .reached_by_epsilon(state)
+                    .into_iter()
+                    .filter(|reached_state| {
+                        automaton.accepting(*reached_state)
+                            || automaton.transitions[*reached_state]
+                                .keys()
+                                .any(|symbol| symbol.is_some())
+                    })
+                    .collect()
+            })
+            .collect();
         let mut visited_masks = HashMap::new();
         let mut transitions = vec![];
         let mut accepting = vec![];
         let mut queue = VecDeque::new();
-        let initial_mask: Vec<_> = (0..ss_nfa.size())
-            .map(|state| state == ss_nfa.initial())
-            .collect();
+        let mut initial_mask = vec![false; automaton.size()];
+        closures[automaton.initial()]
+            .iter()
+            .for_each(|state| initial_mask[*state] = true);
         visited_masks.insert(initial_mask.clone(), 0);
         queue.push_back(initial_mask.clone());
         while !queue.is_empty() {
@@ -411,16 +711,17 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
                 .filter(|(_, has)| *has)
                 .unzip::<usize, bool, Vec<_>, Vec<_>>()
                 .0;
-            accepting.push(states.iter().any(|state| ss_nfa.accepting(*state)));
+            accepting.push(states.iter().any(|state| automaton.accepting(*state)));
             transitions.push(vec![]);
-            for c in ss_nfa.alphabet() {
-                let mut next_mask = vec![false; ss_nfa.size()];
+            for c in automaton.alphabet() {
+                let mut next_mask = vec![false; automaton.size()];
                 states.iter().for_each(|state| {
-                    ss_nfa
+                    automaton
                         .symbol_transitions(*state, c)
                         .iter()
-                        .for_each(|next_state| {
-                            next_mask[*next_state] = true;
+                        .flat_map(|next_state| &closures[*next_state])
+                        .for_each(|reached_state| {
+                            next_mask[*reached_state] = true;
                         });
                 });
                 if !next_mask.iter().any(|x| *x) {
@@ -436,12 +737,7 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
                 ));
             }
         }
-        Automaton::from(
-            automaton.alphabet(),
-            automaton.initial(),
-            accepting,
-            transitions,
-        )
+        Automaton::from(automaton.alphabet(), 0, accepting, transitions)
     }
 
     pub fn complete_dfa_from(automaton: &Automaton<S>) -> Automaton<S> {
@@ -452,8 +748,9 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
         if !dfa.is_complete_dfa() {
             dfa.set_size(dfa.size() + 1);
             let halting_state = dfa.size() - 1;
+            let alphabet = dfa.alphabet().clone();
             for state in 0..dfa.size() {
-                for c in dfa.alphabet() {
+                for c in &alphabet {
                     if dfa.symbol_transitions(state, c).is_empty() {
                         dfa.add_symbol_transition(state, halting_state, c.clone());
                     }
@@ -463,48 +760,113 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
         dfa
     }
 
+    pub fn complement_of(automaton: &Automaton<S>) -> Automaton<S> {
+        let mut cdfa = Automaton::complete_dfa_from(automaton);
+        for state in 0..cdfa.size() {
+            cdfa.set_accepting(state, !cdfa.accepting(state));
+        }
+        cdfa
+    }
+
     pub fn minimal_complete_dfa_from(automaton: &Automaton<S>) -> Automaton<S> {
         let cdfa = Automaton::complete_dfa_from(automaton);
+        let alphabet = cdfa.alphabet();
         let reached_from_initial = cdfa.reached(cdfa.initial());
-        let mut classes = vec![
-            HashSet::from_iter(
-                reached_from_initial
-                    .clone()
-                    .into_iter()
-                    .filter(|state| cdfa.accepting(*state)),
-            ),
-            HashSet::from_iter(
-                reached_from_initial
-                    .clone()
-                    .into_iter()
-                    .filter(|state| !cdfa.accepting(*state)),
-            ),
-        ];
-        for _len in 1..cdfa.size() {
-            for c in cdfa.alphabet() {
-                classes = classes
-                    .into_iter()
-                    .map(|class| {
-                        let mut split_classes: HashMap<usize, HashSet<usize>> = HashMap::new();
-                        class.into_iter().for_each(|state| {
-                            split_classes
-                                .entry(cdfa.symbol_transitions(state, c)[0])
-                                .or_default()
-                                .insert(state);
-                        });
-                        split_classes.into_values().collect::<Vec<_>>()
-                    })
-                    .flatten()
-                    .collect();
+        let next_states: Vec<Vec<usize>> = (0..cdfa.size())
+            .map(|state| {
+                alphabet
+                    .iter()
+                    .map(|c| cdfa.symbol_transitions(state, c)[0])
+                    .collect()
+            })
+            .collect();
+        let mut inverse_transitions = vec![vec![vec![]; cdfa.size()]; alphabet.len()];
+        for state in &reached_from_initial {
+            for (symbol, next_state) in next_states[*state].iter().enumerate() {
+                inverse_transitions[symbol][*next_state].push(*state);
             }
         }
-        classes.sort_by_cached_key(|class| {
-            class
-                .clone()
+        // Hopcroft's partition refinement. Blocks are contiguous ranges of `elements`;
+        // while splitting, the marked states of a block are moved to its front.
+        let mut elements: Vec<usize> = reached_from_initial
+            .iter()
+            .filter(|state| cdfa.accepting(**state))
+            .chain(
+                reached_from_initial
+                    .iter()
+                    .filter(|state| !cdfa.accepting(**state)),
+            )
+            .cloned()
+            .collect();
+        let accepting_count = elements
+            .iter()
+            .filter(|state| cdfa.accepting(**state))
+            .count();
+        let mut blocks: Vec<(usize, usize)> =
+            [(0, accepting_count), (accepting_count, elements.len())]
                 .into_iter()
-                .min()
-                .expect("all classes are non-empty")
-        });
+                .filter(|(start, end)| start < end)
+                .collect();
+        let mut block_of = vec![0; cdfa.size()];
+        let mut position = vec![0; cdfa.size()];
+        for (block, (start, end)) in blocks.iter().enumerate() {
+            for (index, state) in elements.iter().enumerate().take(*end).skip(*start) {
+                block_of[*state] = block;
+                position[*state] = index;
+            }
+        }
+        let mut marked = vec![0; blocks.len()];
+        let mut in_worklist = vec![true; blocks.len()];
+        let mut worklist: Vec<usize> = (0..blocks.len()).collect();
+        while let Some(splitter) = worklist.pop() {
+            in_worklist[splitter] = false;
+            let splitter_states = elements[blocks[splitter].0..blocks[splitter].1].to_vec();
+            for inverse_symbol_transitions in &inverse_transitions {
+                let mut touched_blocks = vec![];
+                for state in &splitter_states {
+                    for previous_state in &inverse_symbol_transitions[*state] {
+                        let block = block_of[*previous_state];
+                        let marked_position = blocks[block].0 + marked[block];
+                        let moved_state = elements[marked_position];
+                        elements.swap(position[*previous_state], marked_position);
+                        position[moved_state] = position[*previous_state];
+                        position[*previous_state] = marked_position;
+                        marked[block] += 1;
+                        if marked[block] == 1 {
+                            touched_blocks.push(block);
+                        }
+                    }
+                }
+                for block in touched_blocks {
+                    let (start, end) = blocks[block];
+                    let split = start + marked[block];
+                    marked[block] = 0;
+                    if split == end {
+                        continue;
+                    }
+                    let new_block = blocks.len();
+                    blocks.push((start, split));
+                    blocks[block] = (split, end);
+                    marked.push(0);
+                    elements[start..split]
+                        .iter()
+                        .for_each(|state| block_of[*state] = new_block);
+                    if in_worklist[block] || split - start <= end - split {
+                        worklist.push(new_block);
+                        in_worklist.push(true);
+                    } else {
+                        worklist.push(block);
+                        in_worklist[block] = true;
+                        in_worklist.push(false);
+                    }
+                }
+            }
+        }
+        let mut classes: Vec<Vec<usize>> = blocks
+            .into_iter()
+            .map(|(start, end)| elements[start..end].to_vec())
+            .collect();
+        classes.sort_by_cached_key(|class| *class.iter().min().expect("all classes are non-empty"));
         let mut class_index = vec![0; cdfa.size()];
         classes.iter().enumerate().for_each(|(index, class)| {
             class.iter().for_each(|state| {
@@ -513,109 +875,99 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
         });
         let accepting = classes
             .iter()
-            .map(|class| class.iter().any(|state| cdfa.accepting(*state)))
+            .map(|class| cdfa.accepting(class[0]))
             .collect();
         let transitions = classes
             .iter()
             .map(|class| {
-                let state = *class.iter().next().expect("all classes are non-empty");
-                cdfa.transitions(state)
-                    .into_iter()
-                    .map(|Transition { next_state, symbol }| Transition {
-                        next_state: class_index[next_state],
-                        symbol,
+                next_states[class[0]]
+                    .iter()
+                    .zip(alphabet.iter())
+                    .map(|(next_state, c)| {
+                        Transition::single_symbol(c.clone(), class_index[*next_state])
                     })
                     .collect::<Vec<_>>()
             })
             .collect();
         Automaton::from(
-            cdfa.alphabet(),
+            alphabet,
             class_index[cdfa.initial()],
             accepting,
             transitions,
         )
     }
 
-    pub fn regex(&self) -> Regex<S> {
-        use Regex::*;
-        let mut regex_transitions = (0..self.size())
+    pub fn trim_of(automaton: &Automaton<S>) -> (Automaton<S>, Vec<Option<usize>>) {
+        let co_reached = automaton.co_reached();
+        let mut useful = vec![false; automaton.size()];
+        automaton
+            .reached(automaton.initial())
             .into_iter()
+            .for_each(|state| useful[state] = co_reached[state]);
+        useful[automaton.initial()] = true;
+        let mut state_map = vec![None; automaton.size()];
+        (0..automaton.size())
+            .filter(|state| useful[*state])
+            .enumerate()
+            .for_each(|(new_state, state)| state_map[state] = Some(new_state));
+        let accepting = (0..automaton.size())
+            .filter(|state| useful[*state])
+            .map(|state| automaton.accepting(state))
+            .collect();
+        let transitions = (0..automaton.size())
+            .filter(|state| useful[*state])
             .map(|state| {
-                let mut transitions: HashMap<usize, Regex<S>> = HashMap::new();
-                self.transitions(state).into_iter().for_each(|transition| {
-                    let current_regex = if let Some(symbol) = transition.symbol {
-                        String(vec![symbol])
-                    } else {
-                        String(vec![])
-                    };
-                    if transitions.contains_key(&transition.next_state) {
-                        let edge_regex = transitions[&transition.next_state].clone();
-                        transitions.insert(
-                            transition.next_state,
-                            Regex::union(edge_regex, current_regex),
-                        );
-                    } else {
-                        transitions.insert(transition.next_state, current_regex);
-                    }
-                });
-                if self.accepting(state) {
-                    transitions.insert(self.size(), String(vec![]));
-                }
-                transitions
+                automaton
+                    .transitions(state)
+                    .into_iter()
+                    .filter_map(|Transition { next_state, symbol }| {
+                        state_map[next_state].map(|next_state| Transition { next_state, symbol })
+                    })
+                    .collect()
             })
-            .collect::<Vec<_>>();
-        for state in 0..self.size() {
-            if state == self.initial() {
-                continue;
+            .collect();
+        let trim = Automaton::from(
+            automaton.alphabet(),
+            state_map[automaton.initial()].expect("initial state is kept"),
+            accepting,
+            transitions,
+        );
+        (trim, state_map)
+    }
+
+    pub fn reverse_of(automaton: &Automaton<S>) -> Automaton<S> {
+        let mut result = Automaton::new(automaton.alphabet(), automaton.size() + 1);
+        result.set_initial(0);
+        result.set_accepting(automaton.initial() + 1, true);
+        for state in 0..automaton.size() {
+            if automaton.accepting(state) {
+                result.add_empty_transition(0, state + 1);
             }
-            let transitions = regex_transitions[state].clone();
-            for from in 0..self.size() {
-                if from == state {
-                    continue;
-                }
-                if regex_transitions[from].contains_key(&state) {
-                    for (to, regex) in &transitions {
-                        if *to == state {
-                            continue;
-                        }
-                        let current_regex = Regex::concat(
-                            regex_transitions[from][&state].clone(),
-                            if regex_transitions[state].contains_key(&state) {
-                                Regex::concat(
-                                    Regex::kleene_star(regex_transitions[state][&state].clone()),
-                                    regex.clone(),
-                                )
-                            } else {
-                                regex.clone()
-                            },
-                        );
-                        if regex_transitions[from].contains_key(to) {
-                            let edge_regex = regex_transitions[from][to].clone();
-                            regex_transitions[from]
-                                .insert(*to, Regex::union(edge_regex, current_regex));
-                        } else {
-                            regex_transitions[from].insert(*to, current_regex);
-                        }
-                    }
-                    regex_transitions[from].remove(&state);
-                }
+            for transition in automaton.transitions(state) {
+                result.add_transition(transition.next_state + 1, state + 1, transition.symbol);
             }
         }
-        if !regex_transitions[self.initial()].contains_key(&self.size()) {
-            panic!("cannot construct regex from automaton that accepts no words");
-        }
-        if regex_transitions[self.initial()].contains_key(&self.initial()) {
-            Regex::concat(
-                Regex::kleene_star(regex_transitions[self.initial()][&self.initial].clone()),
-                regex_transitions[self.initial()][&self.size()].clone(),
-            )
-        } else {
-            regex_transitions[self.initial()][&self.size()].clone()
-        }
+        result
+    }
+
+    pub fn minimal_dfa_brzozowski(automaton: &Automaton<S>) -> Automaton<S> {
+        let reversed_dfa = Automaton::dfa_from(&Automaton::reverse_of(automaton));
+        Automaton::dfa_from(&Automaton::reverse_of(&reversed_dfa))
     }
 
-    pub fn from_regex(alphabet: &'static [S], regex: &Regex<S>) -> Automaton<S> {
-        return match regex {
+    pub fn regex(&self) -> Regex<S> {
+        self.regex_with(EliminationOrder::default())
+    }
+
+    pub fn from_regex(alphabet: impl Into<Alphabet<S>>, regex: &Regex<S>) -> Automaton<S> {
+        let alphabet = &alphabet.into();
+        match regex {
+            Regex::Empty => Automaton::new(alphabet, 1),
+            Regex::Epsilon => {
+                let mut result = Automaton::new(alphabet, 1);
+                result.set_accepting(0, true);
+                result
+            }
             Regex::String(vec) => {
                 let mut result = Automaton::new(alphabet, vec.len() + 1);
                 result.set_initial(0);
@@ -625,33 +977,61 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
                 }
                 result
             }
-            Regex::Concat(lhs, rhs) => {
-                let lhs_automaton = Automaton::from_regex(alphabet, lhs);
-                let rhs_automaton = Automaton::from_regex(alphabet, rhs);
-                let mut result =
-                    Automaton::new(alphabet, lhs_automaton.size() + rhs_automaton.size());
-                result.set_initial(lhs_automaton.initial());
-                for state in 0..lhs_automaton.size() {
-                    for transition in lhs_automaton.transitions(state) {
-                        result.add_transition(state, transition.next_state, transition.symbol);
-                    }
-                    if lhs_automaton.accepting(state) {
-                        result.add_empty_transition(
-                            state,
-                            rhs_automaton.initial() + lhs_automaton.size(),
-                        );
+            Regex::Class(symbols) => {
+                let mut result = Automaton::new(alphabet, 2);
+                result.set_initial(0);
+                result.set_accepting(1, true);
+                for c in alphabet.iter().filter(|c| symbols.contains(c)) {
+                    result.add_symbol_transition(0, 1, c.clone());
+                }
+                result
+            }
+            Regex::NegatedClass(symbols) => {
+                let mut result = Automaton::new(alphabet, 2);
+                result.set_initial(0);
+                result.set_accepting(1, true);
+                for c in alphabet.iter().filter(|c| !symbols.contains(c)) {
+                    result.add_symbol_transition(0, 1, c.clone());
+                }
+                result
+            }
+            Regex::Concat(..) => {
+                // A chain of concatenations, such as a bounded repetition, is built in one pass,
+                // so that the automata of its factors are not copied again at every level.
+                let mut factors = vec![];
+                let mut stack = vec![regex];
+                while let Some(regex) = stack.pop() {
+                    if let Regex::Concat(lhs, rhs) = regex {
+                        stack.push(rhs);
+                        stack.push(lhs);
+                    } else {
+                        factors.push(regex);
                     }
                 }
-                for state in 0..rhs_automaton.size() {
-                    for transition in rhs_automaton.transitions(state) {
-                        result.add_transition(
-                            state + lhs_automaton.size(),
-                            transition.next_state + lhs_automaton.size(),
-                            transition.symbol,
-                        );
+                let mut result = Automaton::from_regex(alphabet, factors[0]);
+                let mut accepting: Vec<usize> = (0..result.size())
+                    .filter(|state| result.accepting(*state))
+                    .collect();
+                for factor in &factors[1..] {
+                    let factor_automaton = Automaton::from_regex(alphabet, factor);
+                    let offset = result.size();
+                    result.set_size(offset + factor_automaton.size());
+                    for state in accepting.drain(..) {
+                        result.set_accepting(state, false);
+                        result.add_empty_transition(state, factor_automaton.initial() + offset);
                     }
-                    if rhs_automaton.accepting(state) {
-                        result.set_accepting(state + lhs_automaton.size(), true);
+                    for state in 0..factor_automaton.size() {
+                        for transition in factor_automaton.transitions(state) {
+                            result.add_transition(
+                                state + offset,
+                                transition.next_state + offset,
+                                transition.symbol,
+                            );
+                        }
+                        if factor_automaton.accepting(state) {
+                            result.set_accepting(state + offset, true);
+                            accepting.push(state + offset);
+                        }
                     }
                 }
                 result
@@ -690,18 +1070,51 @@ impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
                 }
                 result
             }
+            Regex::Intersection(lhs, rhs) => Automaton::intersection(
+                &Automaton::from_regex(alphabet, lhs),
+                &Automaton::from_regex(alphabet, rhs),
+            ),
+            Regex::Complement(regex) => {
+                Automaton::complement_of(&Automaton::from_regex(alphabet, regex))
+            }
             Regex::KleeneStar(regex) => {
-                let mut result = Automaton::from_regex(alphabet, regex);
-                result.set_accepting(result.initial(), true);
-                for state in 0..result.size() {
-                    if result.accepting(state) {
-                        result.add_empty_transition(state, result.initial());
+                let regex_automaton = Automaton::from_regex(alphabet, regex);
+                let mut result = Automaton::new(alphabet, 1 + regex_automaton.size());
+                result.set_initial(0);
+                result.set_accepting(0, true);
+                result.add_empty_transition(0, regex_automaton.initial() + 1);
+                for state in 0..regex_automaton.size() {
+                    for transition in regex_automaton.transitions(state) {
+                        result.add_transition(
+                            state + 1,
+                            transition.next_state + 1,
+                            transition.symbol,
+                        );
+                    }
+                    if regex_automaton.accepting(state) {
+                        result.set_accepting(state + 1, true);
+                        result.add_empty_transition(state + 1, regex_automaton.initial() + 1);
                     }
                 }
                 result
             }
-        };
+        }
     }
 }
 
 pub mod char_automaton;
+mod elimination;
+mod error;
+mod language;
+mod matcher;
+mod product;
+mod search;
+mod simplify;
+mod state_set;
+
+pub use elimination::EliminationOrder;
+pub use error::AutomatonError;
+pub use language::{BigCount, Words};
+pub use matcher::Matcher;
+pub use product::ProductOperation;
+pub use search::{MatchKind, Matches};
@@ -0,0 +1,64 @@
+use super::*;
+
+// Runs an automaton over input supplied in pieces, keeping the epsilon-closed set
+// of states reached by everything fed since the last reset.
+pub struct Matcher<'a, S: Eq + Clone + Hash + 'static> {
+    automaton: &'a Automaton<S>,
+    co_reached: Vec<bool>,
+    states: StateSet,
+    next_states: StateSet,
+}
+
+impl<'a, S: Eq + Clone + Hash + 'static> Matcher<'a, S> {
+    pub fn new(automaton: &'a Automaton<S>) -> Matcher<'a, S> {
+        let mut matcher = Matcher {
+            automaton,
+            co_reached: automaton.co_reached(),
+            states: StateSet::new(automaton.size()),
+            next_states: StateSet::new(automaton.size()),
+        };
+        matcher.reset();
+        matcher
+    }
+
+    pub fn reset(&mut self) {
+        self.states.clear();
+        self.states.insert(self.automaton.initial());
+        self.automaton.close_by_epsilon(&mut self.states);
+    }
+
+    pub fn feed(&mut self, symbol: &S) {
+        if self.states.is_empty() {
+            return;
+        }
+        self.automaton
+            .step(&self.states, symbol, &mut self.next_states);
+        std::mem::swap(&mut self.states, &mut self.next_states);
+    }
+
+    pub fn feed_all(&mut self, word: &[S]) {
+        word.iter().for_each(|symbol| self.feed(symbol));
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.states
+            .states()
+            .iter()
+            .any(|state| self.automaton.accepting(*state))
+    }
+
+    // No continuation of the input fed so far can be accepted.
+    pub fn is_dead(&self) -> bool {
+        !self
+            .states
+            .states()
+            .iter()
+            .any(|state| self.co_reached[*state])
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
+    pub fn matcher(&self) -> Matcher<'_, S> {
+        Matcher::new(self)
+    }
+}
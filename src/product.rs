@@ -0,0 +1,176 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProductOperation {
+    Intersection,
+    Union,
+    Difference,
+    SymmetricDifference,
+}
+
+impl ProductOperation {
+    pub fn accepts(self, lhs: bool, rhs: bool) -> bool {
+        use ProductOperation::*;
+        match self {
+            Intersection => lhs && rhs,
+            Union => lhs || rhs,
+            Difference => lhs && !rhs,
+            SymmetricDifference => lhs != rhs,
+        }
+    }
+
+    // A product state with a stuck component can still accept only if the operation
+    // accepts words rejected by that component.
+    fn may_accept(self, lhs_alive: bool, rhs_alive: bool) -> bool {
+        match (lhs_alive, rhs_alive) {
+            (true, true) => true,
+            (true, false) => self.accepts(true, false),
+            (false, true) => self.accepts(false, true),
+            (false, false) => false,
+        }
+    }
+}
+
+fn successors<S: Eq + Clone + Hash + 'static>(
+    automaton: &Automaton<S>,
+    state: Option<usize>,
+    symbol: &S,
+) -> Vec<Option<usize>> {
+    match state {
+        Some(state) if !automaton.symbol_transitions(state, symbol).is_empty() => automaton
+            .symbol_transitions(state, symbol)
+            .iter()
+            .map(|next_state| Some(*next_state))
+            .collect(),
+        _ => vec![None],
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
+    pub fn product_from(
+        lhs: &Automaton<S>,
+        rhs: &Automaton<S>,
+        operation: ProductOperation,
+    ) -> Automaton<S> {
+        use ProductOperation::*;
+        Automaton::check_same_alphabet(lhs, rhs);
+        let lhs = match operation {
+            SymmetricDifference => Automaton::complete_dfa_from(lhs),
+            _ => Automaton::single_symbol_nfa_from(lhs),
+        };
+        let rhs = match operation {
+            Difference | SymmetricDifference => Automaton::complete_dfa_from(rhs),
+            _ => Automaton::single_symbol_nfa_from(rhs),
+        };
+        let initial_pair = (Some(lhs.initial()), Some(rhs.initial()));
+        let mut visited_pairs = HashMap::from([(initial_pair, 0)]);
+        let mut pairs = vec![initial_pair];
+        let mut transitions = vec![];
+        let mut queue = VecDeque::from([initial_pair]);
+        while let Some(pair) = queue.pop_front() {
+            let mut current_transitions = vec![];
+            for c in lhs.alphabet() {
+                for next_lhs in successors(&lhs, pair.0, c) {
+                    for next_rhs in successors(&rhs, pair.1, c) {
+                        if !operation.may_accept(next_lhs.is_some(), next_rhs.is_some()) {
+                            continue;
+                        }
+                        let next_pair = (next_lhs, next_rhs);
+                        let next_state = *visited_pairs.entry(next_pair).or_insert_with(|| {
+                            pairs.push(next_pair);
+                            queue.push_back(next_pair);
+                            pairs.len() - 1
+                        });
+                        current_transitions.push(Transition::single_symbol(c.clone(), next_state));
+                    }
+                }
+            }
+            transitions.push(current_transitions);
+        }
+        let accepting = pairs
+            .iter()
+            .map(|(lhs_state, rhs_state)| {
+                operation.accepts(
+                    lhs_state.is_some_and(|state| lhs.accepting(state)),
+                    rhs_state.is_some_and(|state| rhs.accepting(state)),
+                )
+            })
+            .collect();
+        Automaton::from(lhs.alphabet(), 0, accepting, transitions)
+    }
+
+    pub fn product_dfa_from(
+        lhs: &Automaton<S>,
+        rhs: &Automaton<S>,
+        operation: ProductOperation,
+    ) -> Automaton<S> {
+        Automaton::check_same_alphabet(lhs, rhs);
+        let lhs = Automaton::single_symbol_nfa_from(lhs);
+        let rhs = Automaton::single_symbol_nfa_from(rhs);
+        let initial_masks: (Vec<_>, Vec<_>) = (
+            (0..lhs.size())
+                .map(|state| state == lhs.initial())
+                .collect(),
+            (0..rhs.size())
+                .map(|state| state == rhs.initial())
+                .collect(),
+        );
+        let mut visited_masks = HashMap::from([(initial_masks.clone(), 0)]);
+        let mut transitions = vec![];
+        let mut accepting = vec![];
+        let mut queue = VecDeque::from([initial_masks]);
+        while let Some(masks) = queue.pop_front() {
+            let lhs_states: Vec<usize> = (0..lhs.size()).filter(|state| masks.0[*state]).collect();
+            let rhs_states: Vec<usize> = (0..rhs.size()).filter(|state| masks.1[*state]).collect();
+            accepting.push(operation.accepts(
+                lhs_states.iter().any(|state| lhs.accepting(*state)),
+                rhs_states.iter().any(|state| rhs.accepting(*state)),
+            ));
+            transitions.push(vec![]);
+            for c in lhs.alphabet() {
+                let mut next_masks = (vec![false; lhs.size()], vec![false; rhs.size()]);
+                lhs_states.iter().for_each(|state| {
+                    lhs.symbol_transitions(*state, c)
+                        .iter()
+                        .for_each(|next_state| next_masks.0[*next_state] = true);
+                });
+                rhs_states.iter().for_each(|state| {
+                    rhs.symbol_transitions(*state, c)
+                        .iter()
+                        .for_each(|next_state| next_masks.1[*next_state] = true);
+                });
+                if !operation.may_accept(
+                    next_masks.0.iter().any(|x| *x),
+                    next_masks.1.iter().any(|x| *x),
+                ) {
+                    continue;
+                }
+                if !visited_masks.contains_key(&next_masks) {
+                    visited_masks.insert(next_masks.clone(), visited_masks.len());
+                    queue.push_back(next_masks.clone());
+                }
+                transitions[visited_masks[&masks]].push(Transition::single_symbol(
+                    c.clone(),
+                    visited_masks[&next_masks],
+                ));
+            }
+        }
+        Automaton::from(lhs.alphabet(), 0, accepting, transitions)
+    }
+
+    pub fn intersection(lhs: &Automaton<S>, rhs: &Automaton<S>) -> Automaton<S> {
+        Automaton::product_from(lhs, rhs, ProductOperation::Intersection)
+    }
+
+    pub fn union(lhs: &Automaton<S>, rhs: &Automaton<S>) -> Automaton<S> {
+        Automaton::product_from(lhs, rhs, ProductOperation::Union)
+    }
+
+    pub fn difference(lhs: &Automaton<S>, rhs: &Automaton<S>) -> Automaton<S> {
+        Automaton::product_from(lhs, rhs, ProductOperation::Difference)
+    }
+
+    pub fn symmetric_difference(lhs: &Automaton<S>, rhs: &Automaton<S>) -> Automaton<S> {
+        Automaton::product_from(lhs, rhs, ProductOperation::SymmetricDifference)
+    }
+}
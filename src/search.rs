@@ -0,0 +1,188 @@
+use super::*;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchKind {
+    // Among the matches with the leftmost start, the one ending last.
+    LeftmostLongest,
+    // Among the matches with the leftmost start, the shortest one.
+    LeftmostShortest,
+}
+
+// Simulates the automaton from every start position at once. Each active state
+// remembers the leftmost start it was reached from, and the list of active
+// states is kept sorted by start, so the first accepting one gives the leftmost match.
+pub(crate) struct Searcher<'a, S: Eq + Clone + Hash + 'static> {
+    automaton: &'a Automaton<S>,
+    co_reached: Vec<bool>,
+    states: StateSet,
+    starts: Vec<usize>,
+    next_states: StateSet,
+    next_starts: Vec<usize>,
+}
+
+fn add_thread<S: Eq + Clone + Hash + 'static>(
+    automaton: &Automaton<S>,
+    states: &mut StateSet,
+    starts: &mut [usize],
+    state: usize,
+    start: usize,
+) {
+    if !states.insert(state) {
+        return;
+    }
+    starts[state] = start;
+    let mut index = states.states().len() - 1;
+    while index < states.states().len() {
+        let current_state = states.states()[index];
+        for next_state in automaton.empty_transitions(current_state) {
+            if states.insert(*next_state) {
+                starts[*next_state] = start;
+            }
+        }
+        index += 1;
+    }
+}
+
+impl<'a, S: Eq + Clone + Hash + 'static> Searcher<'a, S> {
+    pub(crate) fn new(automaton: &'a Automaton<S>) -> Searcher<'a, S> {
+        Searcher {
+            automaton,
+            co_reached: automaton.co_reached(),
+            states: StateSet::new(automaton.size()),
+            starts: vec![0; automaton.size()],
+            next_states: StateSet::new(automaton.size()),
+            next_starts: vec![0; automaton.size()],
+        }
+    }
+
+    fn find_at(&mut self, haystack: &[S], from: usize, kind: MatchKind) -> Option<Range<usize>> {
+        let automaton = self.automaton;
+        let mut found: Option<Range<usize>> = None;
+        self.states.clear();
+        for position in from..=haystack.len() {
+            if found.is_none() {
+                add_thread(
+                    automaton,
+                    &mut self.states,
+                    &mut self.starts,
+                    automaton.initial(),
+                    position,
+                );
+            }
+            if let Some(state) = self
+                .states
+                .states()
+                .iter()
+                .find(|state| automaton.accepting(**state))
+            {
+                let start = self.starts[*state];
+                if found.as_ref().is_none_or(|found| {
+                    start < found.start
+                        || (start == found.start && kind == MatchKind::LeftmostLongest)
+                }) {
+                    found = Some(start..position);
+                }
+            }
+            if position == haystack.len() || (self.states.is_empty() && found.is_some()) {
+                break;
+            }
+            self.next_states.clear();
+            for state in self.states.states() {
+                let start = self.starts[*state];
+                // Threads that started later than the match found so far cannot beat it.
+                if !self.co_reached[*state]
+                    || found.as_ref().is_some_and(|found| {
+                        start > found.start
+                            || (start == found.start && kind == MatchKind::LeftmostShortest)
+                    })
+                {
+                    continue;
+                }
+                for next_state in automaton.symbol_transitions(*state, &haystack[position]) {
+                    add_thread(
+                        automaton,
+                        &mut self.next_states,
+                        &mut self.next_starts,
+                        *next_state,
+                        start,
+                    );
+                }
+            }
+            std::mem::swap(&mut self.states, &mut self.next_states);
+            std::mem::swap(&mut self.starts, &mut self.next_starts);
+        }
+        found
+    }
+
+    // The next match at or after `position`, which is moved past it.
+    pub(crate) fn next_match(
+        &mut self,
+        haystack: &[S],
+        position: &mut usize,
+        kind: MatchKind,
+    ) -> Option<Range<usize>> {
+        if *position > haystack.len() {
+            return None;
+        }
+        match self.find_at(haystack, *position, kind) {
+            Some(found) => {
+                // Step past an empty match so that the search makes progress.
+                *position = if found.is_empty() {
+                    found.end + 1
+                } else {
+                    found.end
+                };
+                Some(found)
+            }
+            None => {
+                *position = haystack.len() + 1;
+                None
+            }
+        }
+    }
+}
+
+pub struct Matches<'a, 'h, S: Eq + Clone + Hash + 'static> {
+    searcher: Searcher<'a, S>,
+    haystack: &'h [S],
+    position: usize,
+    kind: MatchKind,
+}
+
+impl<S: Eq + Clone + Hash + 'static> Iterator for Matches<'_, '_, S> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        self.searcher
+            .next_match(self.haystack, &mut self.position, self.kind)
+    }
+}
+
+impl<S: Eq + Clone + Hash + 'static> Automaton<S> {
+    pub fn find(&self, haystack: &[S]) -> Option<Range<usize>> {
+        self.find_with(haystack, MatchKind::LeftmostLongest)
+    }
+
+    pub fn find_with(&self, haystack: &[S], kind: MatchKind) -> Option<Range<usize>> {
+        Searcher::new(self).find_at(haystack, 0, kind)
+    }
+
+    pub fn find_iter<'h>(&self, haystack: &'h [S]) -> Matches<'_, 'h, S> {
+        self.find_iter_with(haystack, MatchKind::LeftmostLongest)
+    }
+
+    pub fn find_iter_with<'h>(&self, haystack: &'h [S], kind: MatchKind) -> Matches<'_, 'h, S> {
+        Matches {
+            searcher: Searcher::new(self),
+            haystack,
+            position: 0,
+            kind,
+        }
+    }
+
+    pub fn is_match_anywhere(&self, haystack: &[S]) -> bool {
+        self.find_with(haystack, MatchKind::LeftmostShortest)
+            .is_some()
+    }
+}
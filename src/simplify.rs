@@ -0,0 +1,282 @@
+use super::*;
+
+fn nullable<S: Eq + Clone + Hash + 'static>(regex: &Regex<S>) -> bool {
+    match regex {
+        Regex::Epsilon => true,
+        Regex::String(vec) => vec.is_empty(),
+        Regex::Empty | Regex::Class(_) | Regex::NegatedClass(_) => false,
+        Regex::Concat(lhs, rhs) => nullable(lhs) && nullable(rhs),
+        Regex::Union(lhs, rhs) => nullable(lhs) || nullable(rhs),
+        Regex::Intersection(lhs, rhs) => nullable(lhs) && nullable(rhs),
+        Regex::Complement(regex) => !nullable(regex),
+        Regex::KleeneStar(_) => true,
+    }
+}
+
+// Splits a regex into concatenated factors, with strings split into single symbols.
+fn push_factors<S: Eq + Clone + Hash + 'static>(regex: Regex<S>, factors: &mut Vec<Regex<S>>) {
+    match regex {
+        Regex::Concat(lhs, rhs) => {
+            push_factors(*lhs, factors);
+            push_factors(*rhs, factors);
+        }
+        Regex::String(vec) => factors.extend(vec.into_iter().map(|c| Regex::String(vec![c]))),
+        Regex::Epsilon => {}
+        regex => factors.push(regex),
+    }
+}
+
+fn factors<S: Eq + Clone + Hash + 'static>(regex: Regex<S>) -> Vec<Regex<S>> {
+    let mut factors = vec![];
+    push_factors(regex, &mut factors);
+    factors
+}
+
+fn concat_all<S: Eq + Clone + Hash + 'static>(
+    factors: impl IntoIterator<Item = Regex<S>>,
+) -> Regex<S> {
+    factors.into_iter().fold(Regex::Epsilon, Regex::concat)
+}
+
+// Splits a regex into alternatives, dropping the empty ones.
+fn push_alternatives<S: Eq + Clone + Hash + 'static>(
+    regex: Regex<S>,
+    alternatives: &mut Vec<Regex<S>>,
+) {
+    match regex {
+        Regex::Union(lhs, rhs) => {
+            push_alternatives(*lhs, alternatives);
+            push_alternatives(*rhs, alternatives);
+        }
+        regex if regex.is_empty_language() => {}
+        regex => alternatives.push(regex),
+    }
+}
+
+// Splits a regex into the operands of an intersection.
+fn intersected<S: Eq + Clone + Hash + 'static>(regex: Regex<S>) -> Vec<Regex<S>> {
+    match regex {
+        Regex::Intersection(lhs, rhs) => [intersected(*lhs), intersected(*rhs)].concat(),
+        regex => vec![regex],
+    }
+}
+
+// For `r r*` and `r* r` returns `r`, so that the regex can be written as `r+`.
+fn plus_base<S: Eq + Clone + Hash + 'static>(regex: &Regex<S>) -> Option<Regex<S>> {
+    if let [first, second] = &factors(regex.clone())[..] {
+        match (first, second) {
+            (base, Regex::KleeneStar(starred)) | (Regex::KleeneStar(starred), base)
+                if **starred == *base =>
+            {
+                return Some(base.clone());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn simplify_step<S: Eq + Clone + Hash + Ord + 'static>(regex: &Regex<S>) -> Regex<S> {
+    match regex {
+        Regex::Empty | Regex::Epsilon => regex.clone(),
+        Regex::String(vec) if vec.is_empty() => Regex::Epsilon,
+        Regex::String(_) => regex.clone(),
+        Regex::Class(symbols) => {
+            let mut symbols = symbols.clone();
+            symbols.sort();
+            symbols.dedup();
+            if symbols.is_empty() {
+                Regex::Empty
+            } else if symbols.len() == 1 {
+                Regex::String(symbols)
+            } else {
+                Regex::Class(symbols)
+            }
+        }
+        Regex::NegatedClass(symbols) => {
+            let mut symbols = symbols.clone();
+            symbols.sort();
+            symbols.dedup();
+            Regex::NegatedClass(symbols)
+        }
+        Regex::Concat(..) => {
+            let mut simplified_factors: Vec<Regex<S>> = vec![];
+            for factor in factors(regex.clone()) {
+                for factor in factors(simplify_step(&factor)) {
+                    if factor.is_empty_language() {
+                        return Regex::Empty;
+                    }
+                    // r* r* = r*
+                    if matches!(factor, Regex::KleeneStar(_))
+                        && simplified_factors.last() == Some(&factor)
+                    {
+                        continue;
+                    }
+                    simplified_factors.push(factor);
+                }
+            }
+            concat_all(simplified_factors)
+        }
+        Regex::Union(..) => {
+            let mut alternatives = vec![];
+            push_alternatives(regex.clone(), &mut alternatives);
+            simplify_union(alternatives.iter().map(simplify_step).collect())
+        }
+        Regex::Intersection(..) => {
+            let mut operands = vec![];
+            for operand in intersected(regex.clone()) {
+                operands.extend(intersected(simplify_step(&operand)));
+            }
+            // r&r = r
+            operands.sort();
+            operands.dedup();
+            operands
+                .into_iter()
+                .reduce(Regex::intersection)
+                .expect("intersection has operands")
+        }
+        Regex::Complement(inner) => match simplify_step(inner) {
+            // ~~r = r
+            Regex::Complement(inner) => *inner,
+            inner => Regex::complement(inner),
+        },
+        Regex::KleeneStar(inner) => simplify_star(simplify_step(inner)),
+    }
+}
+
+fn simplify_star<S: Eq + Clone + Hash + Ord + 'static>(inner: Regex<S>) -> Regex<S> {
+    if inner.is_epsilon() || inner.is_empty_language() {
+        return Regex::Epsilon;
+    }
+    if let Regex::KleeneStar(_) = inner {
+        return inner;
+    }
+    if let Some(base) = plus_base(&inner) {
+        return simplify_star(base);
+    }
+    if let Regex::Union(..) = inner {
+        // (ε|r)* = r* and (r*|s)* = (r|s)*
+        let mut alternatives = vec![];
+        push_alternatives(inner, &mut alternatives);
+        let alternatives = alternatives
+            .into_iter()
+            .filter(|alternative| !alternative.is_epsilon())
+            .map(|alternative| match alternative {
+                Regex::KleeneStar(starred) => *starred,
+                alternative => alternative,
+            })
+            .collect();
+        let inner = simplify_union(alternatives);
+        if inner.is_epsilon() {
+            return inner;
+        }
+        return Regex::kleene_star(inner);
+    }
+    Regex::kleene_star(inner)
+}
+
+// Replaces the alternatives that share the first (or the last) factor with that factor
+// concatenated with the union of their remainders.
+fn factor_out<S: Eq + Clone + Hash + Ord + 'static>(
+    alternatives: Vec<Regex<S>>,
+    from_start: bool,
+) -> Vec<Regex<S>> {
+    let mut groups: Vec<(Regex<S>, Vec<Regex<S>>)> = vec![];
+    let mut result = vec![];
+    for alternative in alternatives {
+        let mut alternative_factors = factors(alternative.clone());
+        let common = if from_start {
+            alternative_factors.first().cloned()
+        } else {
+            alternative_factors.pop()
+        };
+        let Some(common) = common else {
+            result.push(alternative);
+            continue;
+        };
+        if from_start {
+            alternative_factors.remove(0);
+        }
+        let rest = concat_all(alternative_factors);
+        match groups.iter_mut().find(|(factor, _)| *factor == common) {
+            Some((_, rests)) => rests.push(rest),
+            None => groups.push((common, vec![rest])),
+        }
+    }
+    for (common, mut rests) in groups {
+        if rests.len() == 1 {
+            let rest = rests.pop().expect("group is not empty");
+            result.push(if from_start {
+                Regex::concat(common, rest)
+            } else {
+                Regex::concat(rest, common)
+            });
+            continue;
+        }
+        let rest = simplify_union(rests);
+        if rest.is_empty_language() {
+            continue;
+        }
+        result.push(if from_start {
+            Regex::concat(common, rest)
+        } else {
+            Regex::concat(rest, common)
+        });
+    }
+    result
+}
+
+fn simplify_union<S: Eq + Clone + Hash + Ord + 'static>(alternatives: Vec<Regex<S>>) -> Regex<S> {
+    let mut flattened = vec![];
+    for alternative in alternatives {
+        push_alternatives(alternative, &mut flattened);
+    }
+    let has_epsilon = flattened.iter().any(Regex::is_epsilon);
+    let mut alternatives: Vec<Regex<S>> = flattened
+        .into_iter()
+        .filter(|alternative| !alternative.is_epsilon())
+        .collect();
+    alternatives.sort();
+    alternatives.dedup();
+    // r|r* = r*
+    let stars: Vec<Regex<S>> = alternatives
+        .iter()
+        .filter_map(|alternative| match alternative {
+            Regex::KleeneStar(starred) => Some((**starred).clone()),
+            _ => None,
+        })
+        .collect();
+    alternatives.retain(|alternative| !stars.contains(alternative));
+    alternatives = factor_out(factor_out(alternatives, true), false);
+    alternatives.sort();
+    alternatives.dedup();
+    let mut keep_epsilon = has_epsilon && !alternatives.iter().any(nullable);
+    if keep_epsilon && alternatives.len() == 1 {
+        // ε|r r* = r*
+        if let Some(base) = plus_base(&alternatives[0]) {
+            alternatives[0] = simplify_star(base);
+            keep_epsilon = false;
+        }
+    }
+    if keep_epsilon {
+        alternatives.push(Regex::Epsilon);
+    }
+    alternatives
+        .into_iter()
+        .reduce(Regex::union)
+        .unwrap_or(Regex::Empty)
+}
+
+impl<S: Eq + Clone + Hash + Ord + 'static> Regex<S> {
+    // Applies language-preserving Kleene algebra identities until none of them changes the regex.
+    pub fn simplify(&self) -> Regex<S> {
+        let mut current = simplify_step(self);
+        loop {
+            let next = simplify_step(&current);
+            if next == current {
+                return current;
+            }
+            current = next;
+        }
+    }
+}
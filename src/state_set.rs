@@ -0,0 +1,40 @@
+// A set of automaton states backed by a bitset, with the inserted states also kept
+// in a list so that iterating and clearing cost only as much as the set's size.
+#[derive(Debug, Clone)]
+pub(crate) struct StateSet {
+    bits: Vec<u64>,
+    states: Vec<usize>,
+}
+
+impl StateSet {
+    pub(crate) fn new(size: usize) -> StateSet {
+        StateSet {
+            bits: vec![0; size.div_ceil(64)],
+            states: vec![],
+        }
+    }
+
+    pub(crate) fn insert(&mut self, state: usize) -> bool {
+        let bit = 1 << (state % 64);
+        if self.bits[state / 64] & bit != 0 {
+            return false;
+        }
+        self.bits[state / 64] |= bit;
+        self.states.push(state);
+        true
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for state in self.states.drain(..) {
+            self.bits[state / 64] = 0;
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub(crate) fn states(&self) -> &[usize] {
+        &self.states
+    }
+}
@@ -1,9 +1,9 @@
-use automaton::*;
 use automaton::char_automaton::*;
+use automaton::*;
 
 #[test]
 fn test_dfa() {
-    let mut automaton = Automaton::new(&['a', 'b', 'c'], 2);
+    let mut automaton = Automaton::new(['a', 'b', 'c'], 2);
     automaton.set_initial(0);
     automaton.add_symbol_transition(0, 0, 'a');
     automaton.add_symbol_transition(0, 1, 'b');
@@ -22,7 +22,7 @@ fn test_dfa() {
 
 #[test]
 fn test_nfa() {
-    let mut automaton = Automaton::new(&['a', 'b'], 2);
+    let mut automaton = Automaton::new(['a', 'b'], 2);
     automaton.set_initial(0);
     automaton.add_symbol_transition(0, 0, 'a');
     automaton.add_empty_transition(0, 1);
@@ -41,19 +41,24 @@ fn test_nfa() {
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 enum Binary {
     Zero,
-    One
+    One,
 }
 
 #[test]
 fn test_nfa_with_enum() {
     use Binary::*;
-    let mut automaton = Automaton::new(&[Zero, One], 2);
+    let mut automaton = Automaton::new([Zero, One], 2);
     automaton.set_initial(0);
     automaton.add_symbol_transition(0, 0, Zero);
     automaton.add_empty_transition(0, 1);
     automaton.add_symbol_transition(1, 1, One);
     automaton.set_accepting(1, true);
-    let accepted_words = [vec![], vec![One], vec![Zero, Zero], vec![Zero, One, One, One]];
+    let accepted_words = [
+        vec![],
+        vec![One],
+        vec![Zero, Zero],
+        vec![Zero, One, One, One],
+    ];
     for word in accepted_words {
         assert!(automaton.accepted(&word[..]));
     }
@@ -65,17 +70,17 @@ fn test_nfa_with_enum() {
 
 fn stress_automaton_equivalence(one: &Automaton<char>, two: &Automaton<char>, max_len: usize) {
     assert_eq!(one.alphabet(), two.alphabet());
+    assert_eq!(Automaton::equivalent(one, two), Ok(()));
     assert_eq!(one.accepted(&[]), two.accepted(&[]));
     let mut current_words: Vec<Vec<char>> = vec![vec![]];
     for _ in 1..=max_len {
         current_words = current_words
             .into_iter()
-            .map(|word| {
-                one.alphabet().iter().map(move |c| {
-                    vec![word.clone(), vec![c.clone()]].concat()
-                })
+            .flat_map(|word| {
+                one.alphabet()
+                    .iter()
+                    .map(move |c| [word.clone(), vec![*c]].concat())
             })
-            .flatten()
             .collect();
         for word in &current_words {
             assert_eq!(one.accepted(&word[..]), two.accepted(&word[..]));
@@ -86,17 +91,20 @@ fn stress_automaton_equivalence(one: &Automaton<char>, two: &Automaton<char>, ma
 #[test]
 fn test_nfa_to_ss_nfa() {
     let nfa = Automaton::from(
-        &['a', 'b'],
+        ['a', 'b'],
         0,
         vec![false, true, false, false, false, false],
         vec![
             vec![Transition::single_symbol('a', 1)],
             vec![Transition::empty(2)],
-            vec![Transition::single_symbol('b', 3), Transition::single_symbol('a', 4)],
+            vec![
+                Transition::single_symbol('b', 3),
+                Transition::single_symbol('a', 4),
+            ],
             vec![Transition::single_symbol('a', 2)],
             vec![Transition::single_symbol('a', 5), Transition::empty(1)],
-            vec![Transition::single_symbol('b', 4)]
-        ]
+            vec![Transition::single_symbol('b', 4)],
+        ],
     );
     let ss_nfa = Automaton::single_symbol_nfa_from(&nfa);
     assert!(ss_nfa.is_single_symbol());
@@ -106,16 +114,53 @@ fn test_nfa_to_ss_nfa() {
 #[test]
 fn test_nfa_to_dfa() {
     let nfa = Automaton::from(
-        &['0', '1'],
+        ['0', '1'],
         0,
         vec![false, false, false, false, true],
         vec![
-            vec![Transition::single_symbol('0', 0), Transition::single_symbol('1', 0), Transition::single_symbol('1', 1)],
-            vec![Transition::single_symbol('0', 2), Transition::single_symbol('1', 2)],
-            vec![Transition::single_symbol('0', 3), Transition::single_symbol('1', 3)],
-            vec![Transition::single_symbol('0', 4), Transition::single_symbol('1', 4)],
-            vec![]
-        ]
+            vec![
+                Transition::single_symbol('0', 0),
+                Transition::single_symbol('1', 0),
+                Transition::single_symbol('1', 1),
+            ],
+            vec![
+                Transition::single_symbol('0', 2),
+                Transition::single_symbol('1', 2),
+            ],
+            vec![
+                Transition::single_symbol('0', 3),
+                Transition::single_symbol('1', 3),
+            ],
+            vec![
+                Transition::single_symbol('0', 4),
+                Transition::single_symbol('1', 4),
+            ],
+            vec![],
+        ],
+    );
+    let dfa = Automaton::dfa_from(&nfa);
+    assert!(dfa.is_dfa());
+    stress_automaton_equivalence(&nfa, &dfa, 10);
+}
+
+#[test]
+fn test_nfa_with_nonzero_initial_to_dfa() {
+    let nfa = Automaton::from(
+        ['0', '1'],
+        2,
+        vec![false, true, false],
+        vec![
+            vec![
+                Transition::single_symbol('0', 1),
+                Transition::single_symbol('1', 1),
+            ],
+            vec![],
+            vec![
+                Transition::single_symbol('0', 2),
+                Transition::single_symbol('1', 2),
+                Transition::single_symbol('1', 0),
+            ],
+        ],
     );
     let dfa = Automaton::dfa_from(&nfa);
     assert!(dfa.is_dfa());
@@ -125,16 +170,29 @@ fn test_nfa_to_dfa() {
 #[test]
 fn test_nfa_to_complete_dfa() {
     let nfa = Automaton::from(
-        &['0', '1'],
+        ['0', '1'],
         0,
         vec![false, false, false, false, true],
         vec![
-            vec![Transition::single_symbol('0', 0), Transition::single_symbol('1', 0), Transition::single_symbol('1', 1)],
-            vec![Transition::single_symbol('0', 2), Transition::single_symbol('1', 2)],
-            vec![Transition::single_symbol('0', 3), Transition::single_symbol('1', 3)],
-            vec![Transition::single_symbol('0', 4), Transition::single_symbol('1', 4)],
-            vec![]
-        ]
+            vec![
+                Transition::single_symbol('0', 0),
+                Transition::single_symbol('1', 0),
+                Transition::single_symbol('1', 1),
+            ],
+            vec![
+                Transition::single_symbol('0', 2),
+                Transition::single_symbol('1', 2),
+            ],
+            vec![
+                Transition::single_symbol('0', 3),
+                Transition::single_symbol('1', 3),
+            ],
+            vec![
+                Transition::single_symbol('0', 4),
+                Transition::single_symbol('1', 4),
+            ],
+            vec![],
+        ],
     );
     let complete_dfa = Automaton::complete_dfa_from(&nfa);
     assert!(complete_dfa.is_complete_dfa());
@@ -143,7 +201,7 @@ fn test_nfa_to_complete_dfa() {
 
 #[test]
 fn test_dfa_to_minimal_complete_dfa() {
-    let mut dfa = Automaton::new(&['a', 'b'], 4);
+    let mut dfa = Automaton::new(['a', 'b'], 4);
     dfa.add_symbol_transition(0, 3, 'a');
     dfa.add_symbol_transition(0, 1, 'b');
     dfa.add_symbol_transition(1, 1, 'a');
@@ -163,7 +221,7 @@ fn test_dfa_to_minimal_complete_dfa() {
 
 #[test]
 fn test_to_regex_and_back() {
-    let mut automaton = Automaton::new(&['a', 'b'], 4);
+    let mut automaton = Automaton::new(['a', 'b'], 4);
     automaton.add_symbol_transition(0, 3, 'a');
     automaton.add_symbol_transition(0, 1, 'b');
     automaton.add_symbol_transition(1, 1, 'a');
@@ -175,26 +233,27 @@ fn test_to_regex_and_back() {
     automaton.set_initial(0);
     automaton.set_accepting(3, true);
     let regex = automaton.regex();
-    let automaton_from_regex = Automaton::dfa_from(&Automaton::from_regex(automaton.alphabet(), &regex));
+    let automaton_from_regex =
+        Automaton::dfa_from(&Automaton::from_regex(automaton.alphabet(), &regex));
     stress_automaton_equivalence(&automaton, &automaton_from_regex, 15);
 }
 
 #[test]
 fn test_to_string_and_back() {
-    let mut automaton = Automaton::new(&['a', 'b'], 2);
+    let mut automaton = Automaton::new(['a', 'b'], 2);
     automaton.set_initial(0);
     automaton.add_symbol_transition(0, 0, 'a');
     automaton.add_empty_transition(0, 1);
     automaton.add_symbol_transition(1, 1, 'b');
     automaton.set_accepting(1, true);
     let string = automaton_to_string(&automaton);
-    let automaton_from_string = automaton_from_string(&['a', 'b'], &string);
+    let automaton_from_string = automaton_from_string(['a', 'b'], &string).unwrap();
     stress_automaton_equivalence(&automaton, &automaton_from_string, 15);
 }
 
 #[test]
 fn test_to_string_regex_and_back() {
-    let mut automaton = Automaton::new(&['a', 'b'], 4);
+    let mut automaton = Automaton::new(['a', 'b'], 4);
     automaton.add_symbol_transition(0, 3, 'a');
     automaton.add_symbol_transition(0, 1, 'b');
     automaton.add_symbol_transition(1, 1, 'a');
@@ -207,7 +266,1271 @@ fn test_to_string_regex_and_back() {
     automaton.set_accepting(3, true);
     let regex = automaton.regex();
     let regex_string = format!("{}", regex);
-    let regex_from_string = parse_regex_from_string(&regex_string);
-    let automaton_from_regex = Automaton::dfa_from(&Automaton::from_regex(automaton.alphabet(), &regex_from_string));
+    let regex_from_string = parse_regex_from_string(&regex_string).unwrap();
+    assert!(regex_from_string == regex);
+    let automaton_from_regex = Automaton::dfa_from(&Automaton::from_regex(
+        automaton.alphabet(),
+        &regex_from_string,
+    ));
     stress_automaton_equivalence(&automaton, &automaton_from_regex, 15);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_runtime_alphabet() {
+    let alphabet = Alphabet::new("if else while".split(' ').map(String::from).collect());
+    let mut automaton = Automaton::new(&alphabet, 2);
+    automaton.set_initial(0);
+    automaton.add_symbol_transition(0, 0, alphabet[2].clone());
+    automaton.add_symbol_transition(0, 1, alphabet[0].clone());
+    automaton.add_symbol_transition(1, 1, alphabet[1].clone());
+    automaton.set_accepting(1, true);
+    let mcdfa = Automaton::minimal_complete_dfa_from(&automaton);
+    assert_eq!(mcdfa.alphabet(), &alphabet);
+    let word: Vec<String> = vec!["while".into(), "if".into(), "else".into()];
+    assert!(mcdfa.accepted(&word[..]));
+    let unaccepted_word: Vec<String> = vec!["else".into(), "if".into()];
+    assert!(!mcdfa.accepted(&unaccepted_word[..]));
+    let automaton_from_regex = Automaton::from_regex(mcdfa.alphabet(), &mcdfa.regex());
+    assert!(automaton_from_regex.accepted(&word[..]));
+}
+
+#[test]
+fn test_runtime_char_alphabet() {
+    let symbols: Vec<char> = String::from("ab").chars().collect();
+    let automaton = automaton_from_string(symbols, "0\n1\n0 0 a\n0 1\n1 1 b").unwrap();
+    assert!(accepted_str(&automaton, "aabb"));
+    assert!(!accepted_str(&automaton, "aba"));
+}
+
+fn all_words(alphabet: &[char], max_len: usize) -> Vec<Vec<char>> {
+    Automaton::complement_of(&Automaton::new(alphabet, 1))
+        .words_up_to(max_len)
+        .collect()
+}
+
+fn stress_product(
+    lhs: &Automaton<char>,
+    rhs: &Automaton<char>,
+    product: &Automaton<char>,
+    operation: ProductOperation,
+    max_len: usize,
+) {
+    for word in all_words(lhs.alphabet(), max_len) {
+        assert_eq!(
+            product.accepted(&word[..]),
+            operation.accepts(lhs.accepted(&word[..]), rhs.accepted(&word[..]))
+        );
+    }
+}
+
+#[test]
+fn test_product_operations() {
+    use ProductOperation::*;
+    let lhs = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("(a|b)*abb|ba*").unwrap(),
+    );
+    let rhs = Automaton::from(
+        ['a', 'b'],
+        1,
+        vec![false, true],
+        vec![
+            vec![
+                Transition::single_symbol('a', 1),
+                Transition::single_symbol('b', 0),
+            ],
+            vec![
+                Transition::single_symbol('a', 0),
+                Transition::single_symbol('b', 1),
+            ],
+        ],
+    );
+    for operation in [Intersection, Union, Difference, SymmetricDifference] {
+        let product = Automaton::product_from(&lhs, &rhs, operation);
+        stress_product(&lhs, &rhs, &product, operation, 10);
+        let product = Automaton::product_from(&rhs, &lhs, operation);
+        stress_product(&rhs, &lhs, &product, operation, 10);
+        let product_dfa = Automaton::product_dfa_from(&lhs, &rhs, operation);
+        assert!(product_dfa.is_dfa());
+        stress_product(&lhs, &rhs, &product_dfa, operation, 10);
+    }
+    stress_product(
+        &lhs,
+        &rhs,
+        &Automaton::intersection(&lhs, &rhs),
+        Intersection,
+        8,
+    );
+    stress_product(&lhs, &rhs, &Automaton::union(&lhs, &rhs), Union, 8);
+    stress_product(
+        &lhs,
+        &rhs,
+        &Automaton::difference(&lhs, &rhs),
+        Difference,
+        8,
+    );
+    stress_product(
+        &lhs,
+        &rhs,
+        &Automaton::symmetric_difference(&lhs, &rhs),
+        SymmetricDifference,
+        8,
+    );
+}
+
+#[test]
+fn test_complement() {
+    let nfa = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("a*b|ababa").unwrap());
+    let complement = Automaton::complement_of(&nfa);
+    assert!(complement.is_complete_dfa());
+    for word in all_words(nfa.alphabet(), 10) {
+        assert_ne!(nfa.accepted(&word[..]), complement.accepted(&word[..]));
+    }
+    let empty = Automaton::new(['a', 'b'], 1);
+    let universal = Automaton::complement_of(&empty);
+    for word in all_words(universal.alphabet(), 6) {
+        assert!(universal.accepted(&word[..]));
+    }
+}
+
+#[test]
+fn test_equivalence_counterexample() {
+    let one = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a|b)*b").unwrap());
+    let two = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("a*b|(a|b)*bb").unwrap(),
+    );
+    assert_eq!(Automaton::equivalent(&one, &two), Err(vec!['b', 'a', 'b']));
+    assert_eq!(Automaton::equivalent(&two, &one), Err(vec!['b', 'a', 'b']));
+    let three = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a*b)(a*b)*").unwrap());
+    assert_eq!(Automaton::equivalent(&one, &three), Ok(()));
+    let empty = Automaton::new(['a', 'b'], 1);
+    let epsilon = Automaton::from_regex(['a', 'b'], &Regex::String(vec![]));
+    assert_eq!(Automaton::equivalent(&empty, &epsilon), Err(vec![]));
+}
+
+#[test]
+fn test_nested_kleene_star_from_regex() {
+    let automaton = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a*b)*").unwrap());
+    assert!(accepted_str(&automaton, ""));
+    assert!(accepted_str(&automaton, "abaab"));
+    assert!(!accepted_str(&automaton, "a"));
+    assert!(!accepted_str(&automaton, "aba"));
+}
+
+#[test]
+fn test_subset() {
+    let tight = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("a(ba)*b").unwrap());
+    let loose = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a|b)*b").unwrap());
+    assert_eq!(tight.is_subset_of(&loose), Ok(()));
+    assert_eq!(loose.is_subset_of(&tight), Err(vec!['b']));
+    let odd = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("ab(abab)*").unwrap());
+    assert_eq!(odd.is_subset_of(&tight), Ok(()));
+    assert_eq!(tight.is_subset_of(&odd), Err(vec!['a', 'b', 'a', 'b']));
+    let with_empty = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(ab)*").unwrap());
+    assert_eq!(tight.is_subset_of(&with_empty), Ok(()));
+    assert_eq!(with_empty.is_subset_of(&tight), Err(vec![]));
+    let empty = Automaton::new(['a', 'b'], 1);
+    assert_eq!(empty.is_subset_of(&tight), Ok(()));
+    assert_eq!(tight.is_subset_of(&tight), Ok(()));
+}
+
+#[test]
+fn test_language_queries() {
+    let empty = Automaton::new(['a', 'b'], 2);
+    assert!(empty.is_empty());
+    assert!(!empty.is_universal());
+    assert!(empty.is_finite());
+    assert_eq!(empty.language_size(), Some(BigCount::from(0u32)));
+    let universal = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a|b)*").unwrap());
+    assert!(!universal.is_empty());
+    assert!(universal.is_universal());
+    assert!(!universal.is_finite());
+    assert_eq!(universal.language_size(), None);
+    let ambiguous = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("a(b|bb)|ab(b|a)").unwrap(),
+    );
+    assert!(!ambiguous.is_universal());
+    assert!(ambiguous.is_finite());
+    assert_eq!(ambiguous.language_size(), Some(BigCount::from(3u32)));
+    let epsilon_cycle = Automaton::from(
+        ['a', 'b'],
+        0,
+        vec![false, false, true, false],
+        vec![
+            vec![Transition::empty(1)],
+            vec![Transition::empty(0), Transition::single_symbol('a', 2)],
+            vec![],
+            vec![Transition::single_symbol('b', 3), Transition::empty(2)],
+        ],
+    );
+    assert!(epsilon_cycle.is_finite());
+    assert_eq!(epsilon_cycle.language_size(), Some(BigCount::from(1u32)));
+    let long = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string(&"(a|b)".repeat(70)).unwrap(),
+    );
+    assert_eq!(long.language_size(), Some(BigCount::from(1u32) << 70));
+}
+
+#[test]
+fn test_shortest_words() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("bb*a|ab(a|b)|aaaa").unwrap(),
+    );
+    assert_eq!(automaton.shortest_accepted(), Some(vec!['b', 'a']));
+    assert_eq!(automaton.shortest_rejected(), Some(vec![]));
+    let tie = parse_regex_from_string("bab|bb|ab|aaa").unwrap();
+    assert_eq!(
+        Automaton::from_regex(['a', 'b'], &tie).shortest_accepted(),
+        Some(vec!['a', 'b'])
+    );
+    assert_eq!(
+        Automaton::from_regex(['b', 'a'], &tie).shortest_accepted(),
+        Some(vec!['b', 'b'])
+    );
+    let epsilon_path = Automaton::from(
+        ['a', 'b'],
+        0,
+        vec![false, false, true],
+        vec![
+            vec![Transition::single_symbol('a', 1), Transition::empty(1)],
+            vec![
+                Transition::single_symbol('b', 2),
+                Transition::single_symbol('a', 2),
+            ],
+            vec![],
+        ],
+    );
+    assert_eq!(epsilon_path.shortest_accepted(), Some(vec!['a']));
+    let almost_universal = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("(a|b)(a|b)*|ab|b").unwrap(),
+    );
+    assert_eq!(almost_universal.shortest_rejected(), Some(vec![]));
+    let universal = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a|b)*").unwrap());
+    assert_eq!(universal.shortest_rejected(), None);
+    assert_eq!(
+        Automaton::complement_of(&universal).shortest_accepted(),
+        None
+    );
+    let no_aa = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(b|ab)*").unwrap());
+    assert_eq!(no_aa.shortest_rejected(), Some(vec!['a']));
+}
+
+#[test]
+fn test_words() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("b(a|b)|a*|ba").unwrap(),
+    );
+    let words: Vec<String> = automaton
+        .words()
+        .take(7)
+        .map(|word| word.into_iter().collect())
+        .collect();
+    assert_eq!(words, ["", "a", "aa", "ba", "bb", "aaa", "aaaa"]);
+    let of_length: Vec<Vec<char>> = automaton.words_of_length(2).collect();
+    assert_eq!(of_length, [vec!['a', 'a'], vec!['b', 'a'], vec!['b', 'b']]);
+    assert_eq!(automaton.words_up_to(3).count(), 6);
+    let finite = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("a(b|bb)|ab(b|a)").unwrap(),
+    );
+    assert_eq!(finite.words().count(), 3);
+    assert_eq!(finite.words_of_length(5).count(), 0);
+    assert_eq!(Automaton::new(['a', 'b'], 1).words().next(), None);
+    let sparse = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(aaaaa)*b").unwrap());
+    let sparse_words: Vec<usize> = sparse.words().take(4).map(|word| word.len()).collect();
+    assert_eq!(sparse_words, [1, 6, 11, 16]);
+    let universal_words = all_words(&['a', 'b'], 10);
+    assert_eq!(universal_words.len(), (1 << 11) - 1);
+    assert!(universal_words
+        .windows(2)
+        .all(|pair| { (pair[0].len(), &pair[0]) < (pair[1].len(), &pair[1]) }));
+}
+
+#[test]
+fn test_count_words() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("b(a|b)|a*|ba").unwrap(),
+    );
+    for len in 0..6 {
+        assert_eq!(
+            automaton.count_words(len),
+            Some(automaton.words_of_length(len).count() as u128)
+        );
+        assert_eq!(
+            automaton.count_words_up_to(len),
+            Some(automaton.words_up_to(len).count() as u128)
+        );
+    }
+    let ambiguous = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("(a|b)*a(a|b)*").unwrap(),
+    );
+    assert_eq!(ambiguous.count_words(10), Some((1 << 10) - 1));
+    assert_eq!(ambiguous.count_words_up_to(3), Some(1 + 3 + 7));
+    let universal = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a|b)*").unwrap());
+    assert_eq!(universal.count_words(127), Some(1 << 127));
+    assert_eq!(universal.count_words(128), None);
+    assert_eq!(universal.count_words_up_to(127), Some(u128::MAX));
+    assert_eq!(universal.count_words_up_to(128), None);
+    assert_eq!(universal.count_words_big(200), BigCount::from(1u8) << 200);
+    assert_eq!(
+        universal.count_words_up_to_big(200),
+        (BigCount::from(1u8) << 201) - BigCount::from(1u8)
+    );
+}
+
+#[test]
+fn test_sample_word() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+    let mut rng = StdRng::seed_from_u64(179);
+    let automaton = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("a*b*|bbb(a|b)(a|b)").unwrap(),
+    );
+    assert_eq!(Automaton::new(['a', 'b'], 1).sample_word(3, &mut rng), None);
+    assert_eq!(automaton.sample_word(0, &mut rng), Some(vec![]));
+    let words: Vec<Vec<char>> = automaton.words_of_length(5).collect();
+    assert_eq!(words.len(), 9);
+    let mut frequencies: HashMap<Vec<char>, usize> = HashMap::new();
+    for _ in 0..4500 {
+        let word = automaton
+            .sample_word(5, &mut rng)
+            .expect("length 5 has accepted words");
+        assert!(automaton.accepted(&word[..]));
+        *frequencies.entry(word).or_default() += 1;
+    }
+    assert_eq!(frequencies.len(), words.len());
+    assert!(frequencies
+        .values()
+        .all(|frequency| (400..600).contains(frequency)));
+    let universal = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("(a|b)*").unwrap());
+    assert_eq!(
+        universal.sample_word(300, &mut rng).map(|word| word.len()),
+        Some(300)
+    );
+}
+
+// The number of distinct languages accepted from the reachable states of the complete
+// DFA, which is the size of the minimal complete DFA by the Myhill-Nerode theorem.
+fn residual_language_count(automaton: &Automaton<char>) -> usize {
+    let cdfa = Automaton::complete_dfa_from(automaton);
+    let mut reached = vec![false; cdfa.size()];
+    reached[cdfa.initial()] = true;
+    let mut stack = vec![cdfa.initial()];
+    while let Some(state) = stack.pop() {
+        for transition in cdfa.transitions(state) {
+            if !reached[transition.next_state] {
+                reached[transition.next_state] = true;
+                stack.push(transition.next_state);
+            }
+        }
+    }
+    let mut residuals: Vec<Automaton<char>> = vec![];
+    for state in (0..cdfa.size()).filter(|state| reached[*state]) {
+        let mut residual = cdfa.clone();
+        residual.set_initial(state);
+        if !residuals
+            .iter()
+            .any(|other| Automaton::equivalent(other, &residual).is_ok())
+        {
+            residuals.push(residual);
+        }
+    }
+    residuals.len()
+}
+
+#[test]
+fn test_hopcroft_minimization_against_residuals() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(42);
+    for _ in 0..200 {
+        let size = rng.gen_range(1..12);
+        let mut dfa = Automaton::new(['a', 'b', 'c'], size);
+        dfa.set_initial(rng.gen_range(0..size));
+        for state in 0..size {
+            dfa.set_accepting(state, rng.gen_bool(0.3));
+            for c in ['a', 'b', 'c'] {
+                if rng.gen_bool(0.85) {
+                    dfa.add_symbol_transition(state, rng.gen_range(0..size), c);
+                }
+            }
+        }
+        let mcdfa = Automaton::minimal_complete_dfa_from(&dfa);
+        assert!(mcdfa.is_complete_dfa());
+        assert_eq!(mcdfa.size(), residual_language_count(&dfa));
+        assert_eq!(Automaton::equivalent(&dfa, &mcdfa), Ok(()));
+    }
+}
+
+#[test]
+fn test_hopcroft_minimization_large() {
+    let regex = parse_regex_from_string(&format!("(a|b)*a{}", "(a|b)".repeat(12))).unwrap();
+    let dfa = Automaton::dfa_from(&Automaton::from_regex(['a', 'b'], &regex));
+    let mcdfa = Automaton::minimal_complete_dfa_from(&dfa);
+    assert_eq!(mcdfa.size(), 1 << 13);
+    // Two copies of the DFA with every transition leading into the other copy.
+    let doubled = Automaton::from(
+        dfa.alphabet(),
+        dfa.initial(),
+        (0..2 * dfa.size())
+            .map(|state| dfa.accepting(state % dfa.size()))
+            .collect(),
+        (0..2 * dfa.size())
+            .map(|state| {
+                dfa.transitions(state % dfa.size())
+                    .into_iter()
+                    .map(|transition| Transition {
+                        next_state: transition.next_state
+                            + if state < dfa.size() { dfa.size() } else { 0 },
+                        symbol: transition.symbol,
+                    })
+                    .collect()
+            })
+            .collect(),
+    );
+    assert_eq!(
+        Automaton::minimal_complete_dfa_from(&doubled).size(),
+        1 << 13
+    );
+}
+
+#[test]
+fn test_reverse() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("a*b|abb(ab)*").unwrap(),
+    );
+    let reversed = Automaton::reverse_of(&automaton);
+    for word in all_words(automaton.alphabet(), 9) {
+        let reversed_word: Vec<char> = word.iter().rev().cloned().collect();
+        assert_eq!(
+            automaton.accepted(&word[..]),
+            reversed.accepted(&reversed_word[..])
+        );
+    }
+    let twice = Automaton::reverse_of(&reversed);
+    assert_eq!(Automaton::equivalent(&automaton, &twice), Ok(()));
+}
+
+#[test]
+fn test_brzozowski_minimization() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..100 {
+        let size = rng.gen_range(1..10);
+        let mut nfa = Automaton::new(['a', 'b'], size);
+        nfa.set_accepting(rng.gen_range(0..size), true);
+        for _ in 0..rng.gen_range(0..3 * size) {
+            let symbol = [Some('a'), Some('b'), None][rng.gen_range(0..3)];
+            nfa.add_transition(rng.gen_range(0..size), rng.gen_range(0..size), symbol);
+        }
+        let brzozowski = Automaton::minimal_dfa_brzozowski(&nfa);
+        assert!(brzozowski.is_dfa());
+        assert_eq!(Automaton::equivalent(&nfa, &brzozowski), Ok(()));
+        if !nfa.is_empty() {
+            assert_eq!(
+                Automaton::complete_dfa_from(&brzozowski).size(),
+                Automaton::minimal_complete_dfa_from(&nfa).size()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_trim() {
+    let automaton = Automaton::from(
+        ['a', 'b'],
+        1,
+        vec![false, false, false, true, false, false],
+        vec![
+            vec![Transition::single_symbol('a', 3)],
+            vec![
+                Transition::single_symbol('a', 2),
+                Transition::single_symbol('b', 4),
+            ],
+            vec![Transition::empty(3), Transition::single_symbol('b', 5)],
+            vec![Transition::single_symbol('a', 1)],
+            vec![Transition::single_symbol('a', 4)],
+            vec![],
+        ],
+    );
+    let (trim, state_map) = Automaton::trim_of(&automaton);
+    assert_eq!(state_map, [None, Some(0), Some(1), Some(2), None, None]);
+    assert_eq!(trim.size(), 3);
+    assert_eq!(trim.initial(), 0);
+    assert!(trim.accepting(2));
+    assert_eq!(trim.transitions(1).len(), 1);
+    assert_eq!(Automaton::equivalent(&automaton, &trim), Ok(()));
+    let (empty_trim, empty_state_map) = Automaton::trim_of(&Automaton::from(
+        ['a'],
+        1,
+        vec![false, false],
+        vec![vec![], vec![Transition::single_symbol('a', 0)]],
+    ));
+    assert_eq!(empty_trim.size(), 1);
+    assert!(empty_trim.is_empty());
+    assert_eq!(empty_state_map, [None, Some(0)]);
+}
+
+#[test]
+fn test_accepted_long_words() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("((a|ab)*(a*)*)*b").unwrap(),
+    );
+    let mut word = vec!['a'; 200_000];
+    assert!(!automaton.accepted(&word[..]));
+    word.push('b');
+    assert!(automaton.accepted(&word[..]));
+    word.push('a');
+    assert!(!automaton.accepted(&word[..]));
+    assert!(automaton.accepted_from_state(automaton.initial(), &word[1..word.len() - 1]));
+}
+
+#[test]
+fn test_matcher() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b', 'c'],
+        &parse_regex_from_string("(ab)*c|a*").unwrap(),
+    );
+    let mut matcher = automaton.matcher();
+    assert!(matcher.is_accepting());
+    assert!(!matcher.is_dead());
+    let chunks: [&[char]; 3] = [&['a', 'b'], &['a'], &['b', 'a', 'b']];
+    for chunk in chunks {
+        matcher.feed_all(chunk);
+        assert!(!matcher.is_accepting());
+        assert!(!matcher.is_dead());
+    }
+    matcher.feed(&'c');
+    assert!(matcher.is_accepting());
+    matcher.feed(&'a');
+    assert!(!matcher.is_accepting());
+    assert!(matcher.is_dead());
+    matcher.reset();
+    matcher.feed_all(&['a', 'a', 'a']);
+    assert!(matcher.is_accepting());
+    matcher.feed(&'b');
+    assert!(matcher.is_dead());
+    matcher.reset();
+    matcher.feed_all(&['a', 'b']);
+    assert!(!matcher.is_dead());
+    matcher.feed(&'b');
+    assert!(matcher.is_dead());
+    for word in all_words(automaton.alphabet(), 6) {
+        let mut matcher = Matcher::new(&automaton);
+        matcher.feed_all(&word);
+        assert_eq!(matcher.is_accepting(), automaton.accepted(&word[..]));
+    }
+}
+
+#[test]
+fn test_find() {
+    let automaton = Automaton::from_regex(
+        ['a', 'b', 'c'],
+        &parse_regex_from_string("abc|ab|bcc*").unwrap(),
+    );
+    let text: Vec<char> = "aabccbcab".chars().collect();
+    assert_eq!(automaton.find(&text[..]), Some(1..4));
+    assert_eq!(
+        automaton.find_with(&text[..], MatchKind::LeftmostShortest),
+        Some(1..3)
+    );
+    assert_eq!(
+        automaton.find_iter(&text[..]).collect::<Vec<_>>(),
+        [1..4, 5..7, 7..9]
+    );
+    assert_eq!(
+        automaton
+            .find_iter_with(&text[..], MatchKind::LeftmostShortest)
+            .collect::<Vec<_>>(),
+        [1..3, 5..7, 7..9]
+    );
+    let overlapping =
+        Automaton::from_regex(['a', 'b', 'c'], &parse_regex_from_string("abc|b").unwrap());
+    assert_eq!(
+        overlapping.find_with(&['a', 'b', 'c'][..], MatchKind::LeftmostShortest),
+        Some(0..3)
+    );
+    assert!(automaton.is_match_anywhere(&text[..]));
+    assert!(!automaton.is_match_anywhere(&['a', 'c', 'a'][..]));
+    let optional = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("a*").unwrap());
+    assert_eq!(
+        optional
+            .find_iter(&['b', 'a', 'a', 'b'][..])
+            .collect::<Vec<_>>(),
+        [0..0, 1..3, 3..3, 4..4]
+    );
+    for text in all_words(automaton.alphabet(), 6) {
+        let naive = (0..=text.len()).find_map(|start| {
+            (start..=text.len())
+                .rev()
+                .find(|end| automaton.accepted(&text[start..*end]))
+                .map(|end| start..end)
+        });
+        assert_eq!(automaton.find(&text[..]), naive);
+    }
+    let long_text = vec!['a'; 100_000];
+    let never = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("a*b").unwrap());
+    assert_eq!(never.find(&long_text[..]), None);
+}
+
+#[test]
+fn test_find_str() {
+    let automaton =
+        Automaton::from_regex(['α', 'β', 'x'], &parse_regex_from_string("αβ*").unwrap());
+    let text = "xαββxxα";
+    assert_eq!(find_str(&automaton, text), Some(1..7));
+    assert_eq!(&text[1..7], "αββ");
+    assert_eq!(
+        find_str_with(&automaton, text, MatchKind::LeftmostShortest),
+        Some(1..3)
+    );
+    assert_eq!(
+        find_all_str(&automaton, text).collect::<Vec<_>>(),
+        [1..7, 9..11]
+    );
+    assert_eq!(
+        find_all_str_with(&automaton, text, MatchKind::LeftmostShortest).collect::<Vec<_>>(),
+        [1..3, 9..11]
+    );
+    assert_eq!(find_all_str(&automaton, text).nth(1), Some(9..11));
+    assert!(is_match_anywhere_str(&automaton, text));
+    assert!(!is_match_anywhere_str(&automaton, "xxββ"));
+}
+
+#[test]
+fn test_extended_regex_syntax() {
+    let alphabet = ['a', 'b', 'c', '*', '('];
+    let same = |lhs: &str, rhs: &str| {
+        let lhs = Automaton::from_regex(alphabet, &parse_regex_from_string(lhs).unwrap());
+        let rhs = Automaton::from_regex(alphabet, &parse_regex_from_string(rhs).unwrap());
+        Automaton::equivalent(&lhs, &rhs)
+    };
+    assert_eq!(same("ab+", "abb*"), Ok(()));
+    assert_eq!(same("ab?c", "ac|abc"), Ok(()));
+    assert_eq!(same("(ab){2}", "abab"), Ok(()));
+    assert_eq!(same("a{1,3}", "a|aa|aaa"), Ok(()));
+    assert_eq!(same("a{0,2}b", "b|ab|aab"), Ok(()));
+    assert_eq!(same("a{2,}", "aaa*"), Ok(()));
+    assert_eq!(same("[a-c]", "a|b|c"), Ok(()));
+    assert_eq!(same("[ca]", "a|c"), Ok(()));
+    assert_eq!(same("[^a]", "b|c|\\*|\\("), Ok(()));
+    assert_eq!(same(".", "a|b|c|\\*|\\("), Ok(()));
+    assert_eq!(same("[a\\-c]", "a|\\-|c"), Ok(()));
+    assert_eq!(same("a(ε|b)", "ab?"), Ok(()));
+    assert_eq!(same("a∅|b", "b"), Ok(()));
+    assert_eq!(same("[]|c", "c"), Ok(()));
+    assert!(same("a+", "a*").is_err());
+    let escaped = Automaton::from_regex(alphabet, &parse_regex_from_string("\\*\\(+").unwrap());
+    assert!(accepted_str(&escaped, "*(("));
+    assert!(!accepted_str(&escaped, "*"));
+    let empty = Automaton::from_regex(alphabet, &parse_regex_from_string("∅").unwrap());
+    assert!(empty.is_empty());
+    let epsilon = Automaton::from_regex(alphabet, &parse_regex_from_string("ε").unwrap());
+    assert_eq!(epsilon.language_size(), Some(BigCount::from(1u32)));
+    assert!(accepted_str(&epsilon, ""));
+    let narrow = Automaton::from_regex(['a', 'b'], &parse_regex_from_string("[a-c]").unwrap());
+    assert!(!accepted_str(&narrow, "c"));
+    assert!(!accepted_str(
+        &Automaton::minimal_complete_dfa_from(&narrow),
+        "c"
+    ));
+    assert_eq!(narrow.count_words(1), Some(2));
+    let bounded = Automaton::from_regex(
+        ['a', 'b'],
+        &parse_regex_from_string("(a|b){0,1000}").unwrap(),
+    );
+    assert!(bounded.size() <= 7 * 1000);
+    assert!(accepted_str(&bounded, "abba"));
+}
+
+#[test]
+fn test_regex_parse_errors() {
+    let error = |regex: &str| {
+        let error = parse_regex_from_string(regex).unwrap_err();
+        (error.kind, error.position)
+    };
+    assert_eq!(error("*a"), (RegexParseErrorKind::MissingOperand, 0));
+    assert_eq!(error("a|(+b)"), (RegexParseErrorKind::MissingOperand, 3));
+    assert_eq!(error("a||b"), (RegexParseErrorKind::EmptyExpression, 2));
+    assert_eq!(error("a|"), (RegexParseErrorKind::EmptyExpression, 2));
+    assert_eq!(error(""), (RegexParseErrorKind::EmptyExpression, 0));
+    assert_eq!(error("a()"), (RegexParseErrorKind::EmptyGroup, 1));
+    assert_eq!(error("(a|)"), (RegexParseErrorKind::EmptyExpression, 3));
+    assert_eq!(
+        error("ab)c"),
+        (RegexParseErrorKind::UnmatchedParenthesis, 2)
+    );
+    assert_eq!(
+        error("a(b(c)"),
+        (RegexParseErrorKind::UnclosedParenthesis, 1)
+    );
+    assert_eq!(error("a[bc"), (RegexParseErrorKind::UnclosedClass, 1));
+    assert_eq!(error("a{2"), (RegexParseErrorKind::UnclosedRepetition, 1));
+    assert_eq!(error("a{3,2}"), (RegexParseErrorKind::InvalidRepetition, 1));
+    assert_eq!(error("a{x}"), (RegexParseErrorKind::InvalidRepetition, 1));
+    assert_eq!(error("ab\\"), (RegexParseErrorKind::DanglingEscape, 2));
+    assert_eq!(error("αβ)"), (RegexParseErrorKind::UnmatchedParenthesis, 2));
+    assert_eq!(
+        error("a{1001}"),
+        (RegexParseErrorKind::RepetitionTooLarge, 1)
+    );
+    assert_eq!(
+        error("a{2,100000}"),
+        (RegexParseErrorKind::RepetitionTooLarge, 1)
+    );
+    assert_eq!(
+        error("(a{1000}){1000}"),
+        (RegexParseErrorKind::RepetitionTooLarge, 9)
+    );
+    assert_eq!(
+        error("(a{1000}){50}(b{1000}){50}"),
+        (RegexParseErrorKind::RepetitionTooLarge, 22)
+    );
+    assert_eq!(error("a[bz-a]"), (RegexParseErrorKind::InvalidRange, 3));
+    assert_eq!(error("[\\]-\\-]"), (RegexParseErrorKind::InvalidRange, 1));
+    let deep = "(".repeat(100_000) + "a" + &")".repeat(100_000);
+    assert_eq!(error(&deep), (RegexParseErrorKind::TooDeep, 256));
+    let regex = "a||b";
+    let error = parse_regex_from_string(regex).unwrap_err();
+    assert_eq!(error.to_string(), "empty expression at position 2");
+    assert_eq!(error.render(regex), "a||b\n  ^ empty expression");
+    assert!(parse_regex_from_string("(a|b)*[^a]{1,2}\\)").is_ok());
+    assert!(parse_regex_from_string("a{1000}[a-a]").is_ok());
+    assert!(parse_regex_from_string("(a{1000}){50}b{1000}").is_ok());
+    let nested = "(".repeat(256) + "a" + &")".repeat(256);
+    assert!(parse_regex_from_string(&nested).is_ok());
+}
+
+#[test]
+fn test_fallible_construction() {
+    assert_eq!(
+        Automaton::try_from(['a', 'b'], 0, vec![false, true], vec![vec![]]).err(),
+        Some(AutomatonError::SizeMismatch {
+            accepting: 2,
+            transitions: 1
+        })
+    );
+    assert_eq!(
+        Automaton::try_from(['a', 'b'], 2, vec![false, true], vec![vec![], vec![]]).err(),
+        Some(AutomatonError::StateOutOfBounds { state: 2, size: 2 })
+    );
+    assert_eq!(
+        Automaton::try_from(
+            ['a', 'b'],
+            0,
+            vec![false, true],
+            vec![vec![Transition::single_symbol('c', 1)], vec![]]
+        )
+        .err(),
+        Some(AutomatonError::SymbolNotInAlphabet('c'))
+    );
+    let mut automaton = Automaton::try_from(
+        ['a', 'b'],
+        0,
+        vec![false, true],
+        vec![
+            vec![Transition::single_symbol('a', 1)],
+            vec![Transition::empty(0)],
+        ],
+    )
+    .unwrap();
+    assert!(accepted_str(&automaton, "aa"));
+    assert_eq!(
+        automaton.try_set_size(1),
+        Err(AutomatonError::SizeDecrease {
+            size: 2,
+            new_size: 1
+        })
+    );
+    assert_eq!(
+        automaton.try_set_initial(5),
+        Err(AutomatonError::StateOutOfBounds { state: 5, size: 2 })
+    );
+    assert_eq!(
+        automaton.try_set_accepting(2, true),
+        Err(AutomatonError::StateOutOfBounds { state: 2, size: 2 })
+    );
+    assert_eq!(
+        automaton.try_add_empty_transition(0, 3),
+        Err(AutomatonError::StateOutOfBounds { state: 3, size: 2 })
+    );
+    assert_eq!(
+        automaton.try_add_symbol_transition(0, 1, 'x'),
+        Err(AutomatonError::SymbolNotInAlphabet('x'))
+    );
+    assert_eq!(
+        automaton
+            .try_add_symbol_transition(0, 1, 'x')
+            .unwrap_err()
+            .to_string(),
+        "symbol 'x' is not in the alphabet"
+    );
+    assert!(!accepted_str(&automaton, "b"));
+    assert_eq!(automaton.try_set_size(3), Ok(()));
+    assert_eq!(automaton.try_add_symbol_transition(0, 2, 'b'), Ok(()));
+    assert_eq!(automaton.try_set_accepting(2, true), Ok(()));
+    assert_eq!(automaton.try_add_transition(2, 1, Some('a')), Ok(()));
+    assert_eq!(automaton.try_set_initial(0), Ok(()));
+    assert!(accepted_str(&automaton, "b"));
+    assert!(accepted_str(&automaton, "ba"));
+    assert!(!accepted_str(&automaton, "bb"));
+}
+
+#[test]
+fn test_automaton_from_string_errors() {
+    let text = "# one or more a's, then b's\n\n  0\n# accepting\n1\n\n0  0 a # loop\n\t0 1   # \n1 1 b  \n";
+    let automaton = automaton_from_string(['a', 'b'], text).unwrap();
+    assert_eq!(automaton_to_string(&automaton), "0\n1\n0 0 a\n0 1\n1 1 b\n");
+    assert!(accepted_str(&automaton, "aabb"));
+    assert!(!accepted_str(&automaton, "aba"));
+
+    let nothing_accepted = automaton_from_string(['a', 'b'], "0\n\n0 1 a\n").unwrap();
+    assert_eq!(nothing_accepted.size(), 2);
+    assert!(nothing_accepted.is_empty());
+    let mut empty = Automaton::new(['a', 'b'], 2);
+    empty.add_symbol_transition(0, 1, 'b');
+    let round_trip = automaton_from_string(['a', 'b'], &automaton_to_string(&empty)).unwrap();
+    assert_eq!(
+        automaton_to_string(&round_trip),
+        automaton_to_string(&empty)
+    );
+    let large = automaton_from_string(['a', 'b'], "0\n1048575\n").unwrap();
+    assert_eq!(large.size(), 1 << 20);
+    let greek = automaton_from_string(['α', 'β'], "0\n1\n0 1 β").unwrap();
+    assert!(accepted_str(&greek, "β"));
+
+    let error = |text: &str| {
+        let error = automaton_from_string(['a', 'b'], text).unwrap_err();
+        (error.kind, error.line, error.column)
+    };
+    assert_eq!(error(""), (AutomatonParseErrorKind::MissingInitial, 1, 1));
+    assert_eq!(
+        error("# only a comment\n\n"),
+        (AutomatonParseErrorKind::MissingInitial, 3, 1)
+    );
+    assert_eq!(
+        error("0"),
+        (AutomatonParseErrorKind::MissingAccepting, 2, 1)
+    );
+    assert_eq!(error("x\n1"), (AutomatonParseErrorKind::InvalidState, 1, 1));
+    assert_eq!(
+        error("0 1\n1"),
+        (AutomatonParseErrorKind::TrailingTokens, 1, 3)
+    );
+    assert_eq!(
+        error("0\n1 -2"),
+        (AutomatonParseErrorKind::InvalidState, 2, 3)
+    );
+    assert_eq!(
+        error("0\n1\n0 1 a\n 0"),
+        (AutomatonParseErrorKind::MissingTransitionTarget, 4, 3)
+    );
+    assert_eq!(
+        error("0\n1\n0 y a"),
+        (AutomatonParseErrorKind::InvalidState, 3, 3)
+    );
+    assert_eq!(
+        error("18446744073709551615\n\n"),
+        (AutomatonParseErrorKind::InvalidState, 1, 1)
+    );
+    assert_eq!(
+        error("0\n10000000000\n"),
+        (AutomatonParseErrorKind::InvalidState, 2, 1)
+    );
+    assert_eq!(
+        error("0\n\n0 18446744073709551615 a"),
+        (AutomatonParseErrorKind::InvalidState, 3, 3)
+    );
+    assert_eq!(
+        error("0\n1\n0 1 ab"),
+        (AutomatonParseErrorKind::InvalidSymbol, 3, 5)
+    );
+    assert_eq!(
+        error("0\n1\n0 1 a#"),
+        (AutomatonParseErrorKind::InvalidSymbol, 3, 5)
+    );
+    assert_eq!(
+        error("0 #\n1\n0 #1 a"),
+        (AutomatonParseErrorKind::MissingTransitionTarget, 3, 2)
+    );
+    assert_eq!(
+        error("0\n1\n0 1 c"),
+        (AutomatonParseErrorKind::SymbolNotInAlphabet, 3, 5)
+    );
+    assert_eq!(
+        error("0\n1\n0 1 a b"),
+        (AutomatonParseErrorKind::TrailingTokens, 3, 7)
+    );
+    assert_eq!(
+        automaton_from_string(['a'], "0\n1\n0 1 c")
+            .unwrap_err()
+            .to_string(),
+        "symbol is not in the alphabet at line 3, column 5"
+    );
+}
+
+#[test]
+fn test_regex_display() {
+    let display = |regex: &str| parse_regex_from_string(regex).unwrap().to_string();
+    assert_eq!(display("(a|bc)*"), "(a|bc)*");
+    assert_eq!(display("((a)|((b)(c)))*"), "(a|bc)*");
+    assert_eq!(display("a|(b|c)"), "a|b|c");
+    assert_eq!(display("a(b*c)d"), "ab*cd");
+    assert_eq!(display("(a|b)|c"), "a|b|c");
+    assert_eq!(display("(ab)*c*"), "(ab)*c*");
+    assert_eq!(display("a**"), "a**");
+    assert_eq!(display("a?"), "a|ε");
+    assert_eq!(display("[^a\\-\\]]|."), "[^a\\-\\]]|.");
+    assert_eq!(display("[^a]∅|."), ".");
+    assert_eq!(display("\\*\\(\\\\\\ε"), "\\*\\(\\\\\\ε");
+    assert_eq!(display("[a-z]"), "[a-z]");
+    assert_eq!(display("[abcx]"), "[a-cx]");
+    assert_eq!(display("[^0-9_ab]"), "[^0-9_ab]");
+    assert_eq!(display("[\\]-a]"), "[\\]-a]");
+    assert_eq!(display("[+,\\-]"), "[+-\\-]");
+    let tree = Regex::concat(
+        Regex::kleene_star(Regex::String(vec!['a'])),
+        Regex::String(vec!['b', 'c']),
+    );
+    assert_eq!(tree.to_string(), "a*bc");
+    assert!(parse_regex_from_string(&tree.to_string()).unwrap() == tree);
+    let nested = Regex::concat(
+        Regex::String(vec!['a']),
+        Regex::concat(
+            Regex::kleene_star(Regex::String(vec!['b'])),
+            Regex::String(vec!['c']),
+        ),
+    );
+    assert_eq!(nested.to_string(), "ab*c");
+    assert!(parse_regex_from_string(&nested.to_string()).unwrap() == nested);
+    let symbol = |c: char| Regex::String(vec![c]);
+    let union = Regex::union(symbol('a'), Regex::union(symbol('b'), symbol('c')));
+    assert!(parse_regex_from_string("a|b|c").unwrap() == union);
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(21);
+    for _ in 0..50 {
+        let mut automaton = Automaton::new(['a', 'b', '*', '|'], 4);
+        for _ in 0..7 {
+            let symbol = automaton.alphabet()[rng.gen_range(0..4)];
+            automaton.add_symbol_transition(rng.gen_range(0..4), rng.gen_range(0..4), symbol);
+        }
+        automaton.set_accepting(rng.gen_range(0..4), true);
+        if automaton.is_empty() {
+            continue;
+        }
+        let regex = automaton.regex();
+        let regex_from_string = parse_regex_from_string(&regex.to_string()).unwrap();
+        assert!(regex_from_string == regex);
+        let automaton_from_regex = Automaton::from_regex(automaton.alphabet(), &regex_from_string);
+        assert_eq!(
+            Automaton::equivalent(&automaton, &automaton_from_regex),
+            Ok(())
+        );
+    }
+}
+
+fn random_regex(rng: &mut rand::rngs::StdRng, depth: usize) -> Regex<char> {
+    use rand::Rng;
+    match if depth == 0 {
+        rng.gen_range(0..3)
+    } else {
+        rng.gen_range(0..9)
+    } {
+        0 => Regex::String(vec![]),
+        1 => Regex::String(vec![['a', 'b'][rng.gen_range(0..2)]]),
+        2 => Regex::Class(vec![]),
+        3 => Regex::Concat(
+            Box::new(random_regex(rng, depth - 1)),
+            Box::new(random_regex(rng, depth - 1)),
+        ),
+        4 | 5 => Regex::Union(
+            Box::new(random_regex(rng, depth - 1)),
+            Box::new(random_regex(rng, depth - 1)),
+        ),
+        6 => Regex::Intersection(
+            Box::new(random_regex(rng, depth - 1)),
+            Box::new(random_regex(rng, depth - 1)),
+        ),
+        7 => Regex::Complement(Box::new(random_regex(rng, depth - 1))),
+        _ => Regex::KleeneStar(Box::new(random_regex(rng, depth - 1))),
+    }
+}
+
+#[test]
+fn test_simplify() {
+    let simplify = |regex: &str| {
+        parse_regex_from_string(regex)
+            .unwrap()
+            .simplify()
+            .to_string()
+    };
+    assert_eq!(simplify("ε|a*"), "a*");
+    assert_eq!(simplify("(a*)*"), "a*");
+    assert_eq!(simplify("a|a"), "a");
+    assert_eq!(simplify("b|(a|b)|c"), "a|b|c");
+    assert_eq!(simplify("(ε|a)*"), "a*");
+    assert_eq!(simplify("(a*|b)*"), "(a|b)*");
+    assert_eq!(simplify("ε|aa*"), "a*");
+    assert_eq!(simplify("a|a*"), "a*");
+    assert_eq!(simplify("a*a*"), "a*");
+    assert_eq!(simplify("abc|abd"), "ab(c|d)");
+    assert_eq!(simplify("ba|ca"), "(b|c)a");
+    assert_eq!(simplify("ab|a"), "a(b|ε)");
+    assert_eq!(simplify("a∅|b"), "b");
+    assert_eq!(simplify("∅*"), "ε");
+    assert_eq!(simplify("[ba]|[a]"), "a|[ab]");
+    let epsilon_concat = Regex::Concat(
+        Box::new(Regex::String(vec![])),
+        Box::new(Regex::String(vec!['a'])),
+    );
+    assert_eq!(epsilon_concat.simplify().to_string(), "a");
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(22);
+    for _ in 0..300 {
+        let regex = random_regex(&mut rng, 5);
+        let simplified = regex.simplify();
+        assert!(simplified.simplify() == simplified);
+        let width = |regex: &Regex<char>| {
+            regex
+                .to_string()
+                .chars()
+                .filter(|c| matches!(c, 'a' | 'b'))
+                .count()
+        };
+        assert!(width(&simplified) <= width(&regex));
+        let automaton = Automaton::from_regex(['a', 'b'], &regex);
+        let simplified_automaton = Automaton::from_regex(['a', 'b'], &simplified);
+        assert_eq!(
+            Automaton::equivalent(&automaton, &simplified_automaton),
+            Ok(()),
+            "{} vs {}",
+            regex,
+            simplified
+        );
+    }
+    let mut automaton = Automaton::new(['a', 'b'], 2);
+    automaton.add_symbol_transition(0, 0, 'a');
+    automaton.add_symbol_transition(0, 1, 'b');
+    automaton.add_symbol_transition(1, 1, 'b');
+    automaton.set_accepting(0, true);
+    automaton.set_accepting(1, true);
+    let regex = automaton.regex();
+    let simplified = regex.simplify();
+    assert_eq!(regex.to_string(), "a*(ε|bb*)");
+    assert_eq!(simplified.to_string(), "a*b*");
+    assert_eq!(
+        Automaton::equivalent(&automaton, &Automaton::from_regex(['a', 'b'], &simplified)),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_elimination_order() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    let orders = [
+        EliminationOrder::Index,
+        EliminationOrder::FewestEdges,
+        EliminationOrder::DelgadoMorais,
+    ];
+    let mut divisible_by_seven = Automaton::new(['0', '1'], 7);
+    for state in 0..7 {
+        divisible_by_seven.add_symbol_transition(state, (2 * state) % 7, '0');
+        divisible_by_seven.add_symbol_transition(state, (2 * state + 1) % 7, '1');
+    }
+    divisible_by_seven.set_accepting(0, true);
+    for order in orders {
+        let regex = divisible_by_seven.regex_with(order);
+        let automaton_from_regex = Automaton::from_regex(['0', '1'], &regex);
+        assert_eq!(
+            Automaton::equivalent(&divisible_by_seven, &automaton_from_regex),
+            Ok(())
+        );
+    }
+    let length = |automaton: &Automaton<char>, order| automaton.regex_with(order).to_string().len();
+    assert!(
+        length(&divisible_by_seven, EliminationOrder::DelgadoMorais)
+            < length(&divisible_by_seven, EliminationOrder::Index)
+    );
+    assert_eq!(
+        divisible_by_seven.regex().to_string(),
+        divisible_by_seven
+            .regex_with(EliminationOrder::DelgadoMorais)
+            .to_string()
+    );
+
+    let mut rng = StdRng::seed_from_u64(23);
+    let mut automaton = Automaton::new(['a', 'b'], 20);
+    for state in 0..20 {
+        automaton.add_symbol_transition(state, (state + 1) % 20, 'a');
+        automaton.add_symbol_transition(state, rng.gen_range(0..20), 'b');
+        automaton.set_accepting(state, rng.gen_bool(0.3));
+    }
+    let regex = automaton.regex();
+    // Index order gives millions of chars here.
+    assert!(regex.to_string().len() < 10_000);
+    assert_eq!(
+        Automaton::equivalent(&automaton, &Automaton::from_regex(['a', 'b'], &regex)),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_empty_and_epsilon_regex() {
+    assert!(parse_regex_from_string("ε").unwrap() == Regex::Epsilon);
+    assert!(parse_regex_from_string("∅").unwrap() == Regex::Empty);
+    assert!(parse_regex_from_string("[]").unwrap() == Regex::Empty);
+    assert!(parse_regex_from_string("∅*").unwrap() == Regex::Epsilon);
+    assert!(parse_regex_from_string("a∅b").unwrap() == Regex::Empty);
+    assert!(parse_regex_from_string("∅|a").unwrap() == Regex::String(vec!['a']));
+    assert!(parse_regex_from_string("εa").unwrap() == Regex::String(vec!['a']));
+    assert_eq!(Regex::<char>::Empty.to_string(), "∅");
+    assert_eq!(Regex::<char>::default().to_string(), "ε");
+    assert!(Regex::<char>::Epsilon.is_epsilon() && Regex::<char>::String(vec![]).is_epsilon());
+    assert!(
+        Regex::<char>::Empty.is_empty_language()
+            && Regex::<char>::Class(vec![]).is_empty_language()
+    );
+    assert!(Automaton::from_regex(['a', 'b'], &Regex::Empty).is_empty());
+    assert_eq!(
+        Automaton::from_regex(['a', 'b'], &Regex::Epsilon).shortest_accepted(),
+        Some(vec![])
+    );
+    assert!(Regex::<char>::Class(vec![]).simplify() == Regex::Empty);
+    assert!(
+        Regex::<char>::Concat(Box::new(Regex::String(vec![])), Box::new(Regex::Epsilon)).simplify()
+            == Regex::Epsilon
+    );
+    assert!(
+        Regex::Union(Box::new(Regex::Empty), Box::new(Regex::String(vec!['a']))).simplify()
+            == Regex::String(vec!['a'])
+    );
+
+    let mut nothing = Automaton::new(['a', 'b'], 3);
+    nothing.add_symbol_transition(0, 1, 'a');
+    nothing.add_symbol_transition(2, 2, 'b');
+    nothing.set_accepting(2, true);
+    assert!(nothing.regex() == Regex::Empty);
+    let mut only_empty_word = Automaton::new(['a', 'b'], 2);
+    only_empty_word.add_symbol_transition(0, 1, 'a');
+    only_empty_word.set_accepting(0, true);
+    assert!(only_empty_word.regex() == Regex::Epsilon);
+    for automaton in [nothing, only_empty_word] {
+        let regex = parse_regex_from_string(&automaton.regex().to_string()).unwrap();
+        assert_eq!(
+            Automaton::equivalent(&automaton, &Automaton::from_regex(['a', 'b'], &regex)),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn test_intersection_and_complement_regex() {
+    let letters: Vec<char> = ('a'..='z').collect();
+    let identifiers = Automaton::from_regex(
+        &letters[..],
+        &parse_regex_from_string("[a-z]+&~(if|else|while)").unwrap(),
+    );
+    for word in ["x", "iff", "whil", "elses", "i"] {
+        assert!(accepted_str(&identifiers, word));
+    }
+    for word in ["", "if", "else", "while"] {
+        assert!(!accepted_str(&identifiers, word));
+    }
+    let spaced = Automaton::from_regex(
+        &letters[..],
+        &parse_regex_from_string("[a-z]+ & ~(if|else|while)").unwrap(),
+    );
+    assert_eq!(Automaton::equivalent(&identifiers, &spaced), Ok(()));
+
+    let with_space = ['a', 'b', ' '];
+    let same = |lhs: &str, rhs: &str| {
+        let lhs = Automaton::from_regex(with_space, &parse_regex_from_string(lhs).unwrap());
+        let rhs = Automaton::from_regex(with_space, &parse_regex_from_string(rhs).unwrap());
+        Automaton::equivalent(&lhs, &rhs)
+    };
+    assert_eq!(same("a  |\tb", "a|b"), Ok(()));
+    assert_eq!(same("~ ~ a", "a"), Ok(()));
+    assert_eq!(same("a b|a", "(a\\ b)|a"), Ok(()));
+    assert_eq!(same("a\\ | b", "(a\\ )|b"), Ok(()));
+    assert_eq!(same("[ ]", "\\ "), Ok(()));
+
+    let display = |regex: &str| parse_regex_from_string(regex).unwrap().to_string();
+    assert_eq!(display("a|(b&c)"), "a|b&c");
+    assert_eq!(display("a&(b&c)|d"), "a&b&c|d");
+    assert!(
+        parse_regex_from_string("a&(b&c)").unwrap() == parse_regex_from_string("(a&b)&c").unwrap()
+    );
+    assert_eq!(display("(a|b)&c"), "(a|b)&c");
+    assert_eq!(display("(ab)&(a.)"), "ab&a.");
+    assert_eq!(display("~(a*)"), "~a*");
+    assert_eq!(display("(~a)*"), "(~a)*");
+    assert_eq!(display("(~a)b"), "~ab");
+    assert_eq!(display("~(ab)"), "~(ab)");
+    assert_eq!(display("~~a"), "~~a");
+    assert_eq!(display("\\&\\~"), "\\&\\~");
+    assert_eq!(display("a\\ | b"), "a\\ |b");
+    assert_eq!(display("[ ab]"), "[\\ ab]");
+
+    let alphabet = ['a', 'b'];
+    let words = all_words(&alphabet, 5);
+    let accepts = |regex: &str, word: &[char]| {
+        Automaton::from_regex(alphabet, &parse_regex_from_string(regex).unwrap()).accepted(word)
+    };
+    for word in &words {
+        let has_aa = accepts("(a|b)*aa(a|b)*", word);
+        let ends_with_b = accepts("(a|b)*b", word);
+        assert_eq!(accepts("~((a|b)*aa(a|b)*)", word), !has_aa);
+        assert_eq!(
+            accepts("(a|b)*aa(a|b)*&(a|b)*b", word),
+            has_aa && ends_with_b
+        );
+        assert_eq!(
+            accepts("a|(a|b)*aa(a|b)*&(a|b)*b", word),
+            word == &['a'][..] || (has_aa && ends_with_b)
+        );
+        assert_eq!(accepts("~a*", word), !accepts("a*", word));
+    }
+    let no_aa = parse_regex_from_string("~(.*aa.*)").unwrap();
+    let expected =
+        Automaton::from_regex(alphabet, &parse_regex_from_string("(b|ab)*(a|ε)").unwrap());
+    assert_eq!(
+        Automaton::equivalent(&Automaton::from_regex(alphabet, &no_aa), &expected),
+        Ok(())
+    );
+    assert!(parse_regex_from_string("~~(ab)").unwrap().simplify() == Regex::String(vec!['a', 'b']));
+    assert!(
+        parse_regex_from_string("(ab)&(ab)").unwrap().simplify() == Regex::String(vec!['a', 'b'])
+    );
+
+    let error = |regex: &str| {
+        let error = parse_regex_from_string(regex).unwrap_err();
+        (error.kind, error.position)
+    };
+    assert_eq!(error("~"), (RegexParseErrorKind::MissingOperand, 0));
+    assert_eq!(error("a~|b"), (RegexParseErrorKind::MissingOperand, 1));
+    assert_eq!(error("~*a"), (RegexParseErrorKind::MissingOperand, 0));
+    assert_eq!(error("a&"), (RegexParseErrorKind::EmptyExpression, 2));
+    assert_eq!(error("&a"), (RegexParseErrorKind::EmptyExpression, 0));
+    assert_eq!(error("(a&|b)"), (RegexParseErrorKind::EmptyExpression, 3));
+    assert_eq!(error("b(&a)"), (RegexParseErrorKind::EmptyExpression, 2));
+    assert_eq!(
+        error(&("~".repeat(100_000) + "a")),
+        (RegexParseErrorKind::TooDeep, 256)
+    );
+    assert!(parse_regex_from_string(&("~".repeat(256) + "a")).is_ok());
+}